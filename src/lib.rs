@@ -1,11 +1,71 @@
 //! This is an example token smart contract.
 //!
-//! The contract has a constant total supply of tokens.
 //! The total supply is initialized together with the contract.
+//! Accounts with the minter role can `mint` new tokens, optionally bounded by a maximum supply
+//! given at initialization. Token owners can `burn` their own tokens, and approved spenders
+//! can `burn_from` the accounts that approved them, both decreasing the total supply.
 //!
 //! Any token owner can then `transfer` tokens to other accounts, or `approve` other accounts to use their tokens.
 //! If a Alice has been approved tokens from Bob, then Alice can use `transfer_from` to use Bob's tokens.
 //!
+//! Contracts can be paid with `transfer_and_call`, which notifies the receiving contract and holds
+//! the tokens until the receiver accepts them, or refunds them if the receiver rejects them.
+//!
+//! Ownership is transferred in two steps: the owner proposes a new owner with `propose_owner`,
+//! who then takes over with `accept_ownership`. The owner can also `renounce_ownership`,
+//! which permanently disables every owner-only action.
+//!
+//! In case of an emergency, a pauser can `pause` the contract, which halts all transfers, mints
+//! and burns until a pauser calls `unpause`.
+//!
+//! Freezers can `freeze` accounts, after which the account can neither send nor receive tokens,
+//! and its allowances cannot be used, until it is unfrozen with `unfreeze`.
+//!
+//! A token can be initialized in allowlist mode, in which only verified accounts added with
+//! `add_to_allowlist` can receive tokens. Tokens can always be sent back to the owner.
+//!
+//! The owner can lock tokens for a beneficiary in a vesting schedule with `create_vesting`.
+//! The tokens vest linearly after a cliff, and the beneficiary can `release` the vested part at
+//! any time. The owner can `revoke` a revocable schedule to get the unvested part back.
+//!
+//! Token owners can also `lock` an amount of their own tokens until a block time. Locked tokens
+//! cannot be transferred or burned until then.
+//!
+//! A `snapshot` records all balances and the total supply, such that governance and dividend
+//! contracts can look up past balances with `balance_of_at` and `total_supply_at`, or request
+//! them from another contract with `request_balance_at`.
+//!
+//! For governance, holders `delegate` their voting power to a delegate, possibly themselves.
+//! The voting power of each delegate is checkpointed as tokens move, such that both current and
+//! past votes can be queried with `get_votes` and `get_past_votes`, or requested from another
+//! contract with `request_past_votes`.
+//!
+//! Small teams can also govern the token directly: holders `create_proposal` with an owner
+//! action such as minting or pausing, `cast_vote` weighted by their balance when the proposal
+//! was created, and anyone can `finalize_proposal` after the voting window and
+//! `execute_proposal` once it has passed. The owner sets the voting window, the quorum and the
+//! balance needed to create a proposal with `set_governance_parameters`.
+//!
+//! The owner can share rewards among all holders with `deposit_rewards`. Each holder's share is
+//! tracked with a cumulative reward per token, and is paid out with `claim_rewards`.
+//!
+//! Holders can `stake` tokens to earn staking rewards, which accrue per unit of block time at a
+//! rate set by the owner and are paid from a reserve funded by the owner. Rewards are paid out
+//! with `claim`, and staked tokens are returned with `unstake`, optionally locked for an
+//! unbonding period.
+//!
+//! The owner can configure a fee in basis points with `set_fee_config`, which is taken from every
+//! transfer and paid to a treasury, unless one of the accounts is exempted with `set_fee_exempt`.
+//!
+//! Privileged actions are guarded by roles (admin, minter, pauser, freezer, KYC and snapshot),
+//! which are managed with `grant_role`, `revoke_role` and `renounce_role`. The creator of the
+//! contract starts with every role. The roles of the owner move with `accept_ownership`, and are
+//! revoked by `renounce_ownership`.
+//!
+//! Every change to the ledger or to an allowance emits a `TransferEvent` or `ApprovalEvent`
+//! notification, delivered as a call from the contract to itself, so that the ledger can be
+//! followed from outside. Minted tokens are sent from, and burned tokens to, the contract address.
+//!
 //! The contract is inspired by the ERC20 token contract.\
 //! <https://github.com/ethereum/EIPs/blob/master/EIPS/eip-20.md>
 #![allow(unused_variables)]
@@ -15,15 +75,166 @@ extern crate pbc_contract_codegen;
 
 use create_type_spec_derive::CreateTypeSpec;
 use read_write_rpc_derive::ReadWriteRPC;
-use std::ops::Add;
+use read_write_state_derive::ReadWriteState;
+use std::fmt;
 
-use pbc_contract_common::address::Address;
-use pbc_contract_common::context::ContractContext;
-use pbc_contract_common::events::EventGroup;
+use pbc_contract_common::address::{Address, AddressType, Shortname};
+use pbc_contract_common::context::{CallbackContext, ContractContext};
+use pbc_contract_common::events::{EventGroup, EventGroupBuilder};
 use pbc_contract_common::sorted_vec_map::SortedVecMap;
 
 mod test;
 
+/// Role whose members can grant and revoke every role, unless another admin role has been set.
+pub const ROLE_ADMIN: u8 = 0;
+/// Role whose members can `mint` new tokens.
+pub const ROLE_MINTER: u8 = 1;
+/// Role whose members can `pause` and `unpause` the contract.
+pub const ROLE_PAUSER: u8 = 2;
+/// Role whose members can freeze and unfreeze accounts.
+pub const ROLE_FREEZER: u8 = 3;
+/// Role whose members manage the allowlist of verified holders.
+pub const ROLE_KYC: u8 = 4;
+/// Role whose members can take balance snapshots.
+pub const ROLE_SNAPSHOT: u8 = 5;
+
+/// The number of basis points in one, i.e. a fee rate of `BASIS_POINTS` takes the full amount.
+pub const BASIS_POINTS: u128 = 10_000;
+
+/// The maximum length of the description of a proposal, in bytes.
+pub const MAX_DESCRIPTION_LENGTH: usize = 1024;
+
+/// The quorum of new contracts, in basis points of the circulating supply.
+pub const DEFAULT_QUORUM_BPS: u16 = 400;
+
+/// The balance needed to create a proposal in new contracts, in basis points of the circulating
+/// supply.
+pub const DEFAULT_PROPOSAL_THRESHOLD_BPS: u16 = 100;
+
+/// Scale of `reward_per_token`, such that rewards smaller than one token per token can be
+/// tracked.
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+/// Shortname of the `transfer_event` action, which receives the [`TransferEvent`] notifications.
+pub const TRANSFER_EVENT_SHORTNAME: u32 = 0x20;
+
+/// Shortname of the `approval_event` action, which receives the [`ApprovalEvent`] notifications.
+pub const APPROVAL_EVENT_SHORTNAME: u32 = 0x21;
+
+/// Errors raised by the actions of the token contract.
+///
+/// Each error has a stable numeric code. A failing action panics with a message of the form
+/// `[TOKEN-<code>] <description>`, e.g. `[TOKEN-001] Insufficient balance`, such that clients
+/// can determine the cause of the failure by its code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenError {
+    /// The account does not have enough tokens.
+    InsufficientBalance = 1,
+    /// The spender has not been approved enough tokens.
+    InsufficientAllowance = 2,
+    /// An amount could not be represented as a [`u128`].
+    Overflow = 3,
+    /// The caller is not allowed to perform the action.
+    Unauthorized = 4,
+    /// The contract is paused.
+    Paused = 5,
+    /// The total supply would exceed the maximum supply.
+    MaxSupplyExceeded = 6,
+    /// The receiver cannot receive tokens through the action.
+    InvalidReceiver = 7,
+    /// The current allowance does not match the expected allowance.
+    AllowanceMismatch = 8,
+    /// The account has been frozen.
+    AccountFrozen = 9,
+    /// The receiver is not on the allowlist.
+    NotAllowlisted = 10,
+    /// No vesting schedule exists with the given id.
+    UnknownVestingSchedule = 11,
+    /// The parameters of a vesting schedule are not valid.
+    InvalidVestingSchedule = 12,
+    /// The tokens are locked until a later block time.
+    TokensLocked = 13,
+    /// No snapshot exists with the given id.
+    UnknownSnapshot = 14,
+    /// No proposal exists with the given id.
+    UnknownProposal = 15,
+    /// The voting window of the proposal has ended.
+    VotingClosed = 16,
+    /// The voting window of the proposal has not yet ended.
+    VotingNotEnded = 17,
+    /// The voter has already voted on the proposal.
+    AlreadyVoted = 18,
+    /// The proposal has not passed, or has already been executed.
+    ProposalNotPassed = 19,
+    /// The staking parameters are not valid.
+    InvalidStakingParameters = 20,
+    /// The transfer fee configuration is not valid.
+    InvalidFeeConfig = 21,
+    /// The requested block time is not yet in the past.
+    FutureLookup = 22,
+    /// The governance parameters are not valid.
+    InvalidGovernanceParameters = 23,
+    /// The description of the proposal is too long.
+    DescriptionTooLong = 24,
+    /// The proposer already has a proposal that is open for voting.
+    ProposalAlreadyActive = 25,
+    /// The allowance has expired, and must be approved again with a new expiry.
+    AllowanceExpired = 26,
+    /// The proposal has already been finalized.
+    ProposalAlreadyFinalized = 27,
+}
+
+impl TokenError {
+    /// The stable numeric code of the error.
+    pub fn code(&self) -> u16 {
+        *self as u16
+    }
+
+    /// Human readable description of the error.
+    pub fn description(&self) -> &'static str {
+        match self {
+            TokenError::InsufficientBalance => "Insufficient balance",
+            TokenError::InsufficientAllowance => "Insufficient allowance",
+            TokenError::Overflow => "Arithmetic overflow",
+            TokenError::Unauthorized => "Unauthorized",
+            TokenError::Paused => "Contract is paused",
+            TokenError::MaxSupplyExceeded => "Maximum supply exceeded",
+            TokenError::InvalidReceiver => "Invalid receiver",
+            TokenError::AllowanceMismatch => "Allowance does not match the expected allowance",
+            TokenError::AccountFrozen => "Account is frozen",
+            TokenError::NotAllowlisted => "Receiver is not allowlisted",
+            TokenError::UnknownVestingSchedule => "Unknown vesting schedule",
+            TokenError::InvalidVestingSchedule => "Invalid vesting schedule",
+            TokenError::TokensLocked => "Tokens are locked",
+            TokenError::UnknownSnapshot => "Unknown snapshot",
+            TokenError::UnknownProposal => "Unknown proposal",
+            TokenError::VotingClosed => "Voting has closed",
+            TokenError::VotingNotEnded => "Voting has not ended",
+            TokenError::AlreadyVoted => "Already voted",
+            TokenError::ProposalNotPassed => "Proposal has not passed",
+            TokenError::InvalidStakingParameters => "Invalid staking parameters",
+            TokenError::InvalidFeeConfig => "Invalid fee configuration",
+            TokenError::FutureLookup => "Block time is not in the past",
+            TokenError::InvalidGovernanceParameters => "Invalid governance parameters",
+            TokenError::DescriptionTooLong => "Description is too long",
+            TokenError::ProposalAlreadyActive => "Proposer already has an active proposal",
+            TokenError::AllowanceExpired => "Allowance has expired",
+            TokenError::ProposalAlreadyFinalized => "Proposal has already been finalized",
+        }
+    }
+
+    /// Fails the current action with this error.
+    pub fn raise(self) -> ! {
+        panic!("{}", self)
+    }
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[TOKEN-{:03}] {}", self.code(), self.description())
+    }
+}
+
 /// Custom struct for the state of the contract.
 ///
 /// The "state" attribute is attached.
@@ -37,22 +248,462 @@ mod test;
 /// * `decimals`: [`u8`], the number of decimals the token uses - e.g. 8,
 /// means to divide the token amount by `100000000` to get its user representation.\
 ///
-/// * `owner`: [`Address`], owner of the contract.
+/// * `owner`: [`Option<Address>`], owner of the contract, or `None` if ownership has been renounced.
+///
+/// * `pending_owner`: [`Option<Address>`], the address proposed as the next owner, if any.
 ///
 /// * `total_supply`: [`u128`], current amount of tokens for the TokenContract.
 ///
+/// * `max_supply`: [`Option<u128>`], the cap that `total_supply` can never exceed, if any.
+///
+/// * `paused`: [`bool`], whether token movements are currently halted by a pauser or a proposal.
+///
+/// * `paused_by_governance`: [`bool`], whether the current pause was started by a proposal, such
+///   that a proposal can also end it.
+///
+/// * `roles`: [`SortedVecMap<u8, RoleData>`], the members and admin role of each role.
+///
+/// * `frozen_accounts`: [`Vec<Address>`], accounts that can neither send nor receive tokens.
+///
+/// * `allowlist_enabled`: [`bool`], whether only allowlisted accounts can receive tokens.
+///
+/// * `allowlist`: [`Vec<Address>`], verified accounts that can receive tokens in allowlist mode.
+///
+/// * `vesting_schedules`: [`SortedVecMap<u64, VestingSchedule>`], vesting schedules by id, holding
+///   the tokens that have not yet been released to their beneficiaries.
+///
+/// * `next_vesting_id`: [`u64`], the id given to the next vesting schedule.
+///
+/// * `locks`: [`SortedVecMap<Address, Vec<TokenLock>>`], tokens of each account that cannot be
+///   moved before their unlock time.
+///
+/// * `current_snapshot_id`: [`u64`], the id of the latest snapshot, or 0 if none has been taken.
+///
+/// * `account_snapshots`: [`SortedVecMap<Address, Vec<Checkpoint>>`], the balances of each account
+///   at the snapshots taken before the balance changed.
+///
+/// * `total_supply_snapshots`: [`Vec<Checkpoint>`], the total supply at the snapshots taken before
+///   the total supply changed.
+///
+/// * `delegates`: [`SortedVecMap<Address, Address>`], the address each account has delegated its
+///   voting power to.
+///
+/// * `vote_checkpoints`: [`SortedVecMap<Address, Vec<VoteCheckpoint>>`], the history of the voting
+///   power of each delegate.
+///
+/// * `voting_period`: [`i64`], the length of the voting window of proposals, in block time.
+///
+/// * `quorum_bps`: [`u16`], the share of the circulating supply at the snapshot of a proposal, in basis
+///   points, that must vote on it for it to pass.
+///
+/// * `proposal_threshold_bps`: [`u16`], the share of the circulating supply, in basis points,
+///   that a holder needs to create a proposal.
+///
+/// * `proposals`: [`SortedVecMap<u64, Proposal>`], governance proposals by id.
+///
+/// * `next_proposal_id`: [`u64`], the id given to the next proposal.
+///
+/// * `reward_pool`: [`u128`], deposited rewards that have not yet been claimed.
+///
+/// * `reward_per_token`: [`u128`], the rewards paid per token since the first deposit,
+///   scaled by [`REWARD_PRECISION`].
+///
+/// * `reward_remainder`: [`u128`], the part of the deposited rewards, scaled by
+///   [`REWARD_PRECISION`], that could not be divided evenly and is carried over to the next deposit.
+///
+/// * `reward_paid_per_token`: [`SortedVecMap<Address, u128>`], the value of `reward_per_token`
+///   when the rewards of each account were last settled.
+///
+/// * `unclaimed_rewards`: [`SortedVecMap<Address, u128>`], settled rewards of each account that
+///   have not yet been claimed.
+///
+/// * `staking`: [`StakingLedger`], the staked tokens and staking rewards.
+///
+/// * `fee_config`: [`Option<FeeConfig>`], the fee taken on transfers, if any.
+///
+/// * `fee_exempt`: [`Vec<Address>`], accounts that neither pay nor cause transfer fees.
+///
+/// * `pending_transfers`: [`u128`], tokens sent with `transfer_and_call` that are held by the
+///   contract until the receiving contract has accepted them.
+///
 /// * `balances`: [`SortedVecMap<Address, u128>`], ledger for the accounts associated with the contract.
 ///
-/// * `allowed`: [`SortedVecMap<Address, SortedVecMap<Address, u128>>`], allowance from an owner to a spender.
+/// * `allowed`: [`SortedVecMap<Address, SortedVecMap<Address, Allowance>>`], allowance from an owner to a spender.
 #[state]
 pub struct TokenState {
     name: String,
     decimals: u8,
     symbol: String,
-    owner: Address,
+    owner: Option<Address>,
+    pending_owner: Option<Address>,
     total_supply: u128,
+    max_supply: Option<u128>,
+    paused: bool,
+    paused_by_governance: bool,
+    roles: SortedVecMap<u8, RoleData>,
+    frozen_accounts: Vec<Address>,
+    allowlist_enabled: bool,
+    allowlist: Vec<Address>,
+    vesting_schedules: SortedVecMap<u64, VestingSchedule>,
+    next_vesting_id: u64,
+    locks: SortedVecMap<Address, Vec<TokenLock>>,
+    current_snapshot_id: u64,
+    account_snapshots: SortedVecMap<Address, Vec<Checkpoint>>,
+    total_supply_snapshots: Vec<Checkpoint>,
+    delegates: SortedVecMap<Address, Address>,
+    vote_checkpoints: SortedVecMap<Address, Vec<VoteCheckpoint>>,
+    voting_period: i64,
+    quorum_bps: u16,
+    proposal_threshold_bps: u16,
+    proposals: SortedVecMap<u64, Proposal>,
+    next_proposal_id: u64,
+    reward_pool: u128,
+    reward_per_token: u128,
+    reward_remainder: u128,
+    reward_paid_per_token: SortedVecMap<Address, u128>,
+    unclaimed_rewards: SortedVecMap<Address, u128>,
+    staking: StakingLedger,
+    fee_config: Option<FeeConfig>,
+    fee_exempt: Vec<Address>,
+    pending_transfers: u128,
     balances: SortedVecMap<Address, u128>,
-    allowed: SortedVecMap<Address, SortedVecMap<Address, u128>>,
+    allowed: SortedVecMap<Address, SortedVecMap<Address, Allowance>>,
+}
+
+/// An allowance from an owner to a spender.
+#[derive(ReadWriteState, CreateTypeSpec)]
+pub struct Allowance {
+    /// The amount that the spender is still allowed to withdraw.
+    pub amount: u128,
+    /// The block time after which the allowance can no longer be used, if any.
+    pub expires_at: Option<i64>,
+}
+
+/// The members of a role, and the role that administers it.
+#[derive(ReadWriteState, CreateTypeSpec)]
+pub struct RoleData {
+    /// The role whose members can grant and revoke this role.
+    pub admin_role: u8,
+    /// The addresses that have been granted this role.
+    pub members: Vec<Address>,
+}
+
+/// Tokens that are released linearly to a beneficiary over time.
+///
+/// Nothing can be released before `start + cliff`. After that, the vested amount grows linearly
+/// from `start` until all tokens have vested at `start + duration`.
+#[derive(ReadWriteState, CreateTypeSpec)]
+pub struct VestingSchedule {
+    /// The address that can release the vested tokens.
+    pub beneficiary: Address,
+    /// The amount of tokens that vest over the schedule.
+    pub total_amount: u128,
+    /// The amount of tokens that have already been released to the beneficiary.
+    pub released_amount: u128,
+    /// The block time at which vesting starts.
+    pub start: i64,
+    /// The time after `start` before which nothing can be released.
+    pub cliff: i64,
+    /// The time after `start` at which all tokens have vested.
+    pub duration: i64,
+    /// Whether the owner can revoke the unvested tokens.
+    pub revocable: bool,
+    /// Whether the schedule has been revoked. A revoked schedule has fully vested.
+    pub revoked: bool,
+}
+
+/// An amount of tokens in an account that cannot be moved before `unlock_at`.
+#[derive(ReadWriteState, CreateTypeSpec)]
+pub struct TokenLock {
+    /// The amount of tokens that are locked.
+    pub amount: u128,
+    /// The block time from which the tokens can be moved again.
+    pub unlock_at: i64,
+}
+
+/// A value recorded for a snapshot.
+///
+/// Checkpoints are recorded lazily: the first time a value changes after a snapshot has been
+/// taken, its old value is recorded for that snapshot. The value at a snapshot is thus found in
+/// the first checkpoint at or after the snapshot, or is the current value if there is none.
+#[derive(ReadWriteState, CreateTypeSpec)]
+pub struct Checkpoint {
+    /// The id of the snapshot the value was recorded for.
+    pub snapshot_id: u64,
+    /// The value at the snapshot.
+    pub value: u128,
+}
+
+/// The voting power of a delegate from a block time until the next checkpoint.
+#[derive(ReadWriteState, CreateTypeSpec)]
+pub struct VoteCheckpoint {
+    /// The block time from which the delegate had `votes`.
+    pub from_time: i64,
+    /// The voting power of the delegate.
+    pub votes: u128,
+}
+
+/// An owner action that is run when a proposal is executed.
+#[derive(ReadWriteRPC, ReadWriteState, CreateTypeSpec, Clone)]
+pub enum ProposalAction {
+    /// Mints `amount` new tokens to `to`.
+    #[discriminant(0)]
+    Mint {
+        /// The address to mint tokens to.
+        to: Address,
+        /// The amount to mint.
+        amount: u128,
+    },
+    /// Pauses the contract.
+    #[discriminant(1)]
+    Pause {},
+    /// Unpauses the contract.
+    #[discriminant(2)]
+    Unpause {},
+}
+
+/// The choice of a voter on a proposal.
+#[derive(ReadWriteRPC, CreateTypeSpec)]
+pub enum VoteType {
+    /// Vote against the proposal.
+    #[discriminant(0)]
+    Against {},
+    /// Vote for the proposal.
+    #[discriminant(1)]
+    For {},
+    /// Take part in the vote without supporting either side.
+    #[discriminant(2)]
+    Abstain {},
+}
+
+/// The state of a proposal.
+#[derive(ReadWriteState, CreateTypeSpec, PartialEq, Eq, Debug)]
+pub enum ProposalStatus {
+    /// The proposal is open for voting, or waiting to be finalized.
+    #[discriminant(0)]
+    Active {},
+    /// The proposal received more votes for than against, and can be executed.
+    #[discriminant(1)]
+    Succeeded {},
+    /// The proposal did not receive more votes for than against.
+    #[discriminant(2)]
+    Defeated {},
+    /// The action of the proposal has been run.
+    #[discriminant(3)]
+    Executed {},
+}
+
+/// A governance proposal, voted on with the balances at the time it was created.
+#[derive(ReadWriteState, CreateTypeSpec)]
+pub struct Proposal {
+    /// The address that created the proposal.
+    pub proposer: Address,
+    /// Human readable description of the proposal.
+    pub description: String,
+    /// The action to run if the proposal passes.
+    pub action: ProposalAction,
+    /// The snapshot taken when the proposal was created, which determines the voting weights.
+    pub snapshot_id: u64,
+    /// The last block time at which votes can be cast.
+    pub end: i64,
+    /// The total weight of the votes needed for the proposal to pass.
+    pub quorum: u128,
+    /// The total weight of the votes for the proposal.
+    pub for_votes: u128,
+    /// The total weight of the votes against the proposal.
+    pub against_votes: u128,
+    /// The total weight of the abstaining votes.
+    pub abstain_votes: u128,
+    /// The addresses that have voted.
+    pub voters: Vec<Address>,
+    /// The state of the proposal.
+    pub status: ProposalStatus,
+}
+
+/// A fee taken on every transfer, paid to a treasury.
+#[derive(ReadWriteRPC, ReadWriteState, CreateTypeSpec)]
+pub struct FeeConfig {
+    /// The fee in basis points of the transferred amount, at most [`BASIS_POINTS`].
+    pub rate_bps: u16,
+    /// The address that receives the fees.
+    pub recipient: Address,
+}
+
+/// Tokens staked by holders, and the rewards paid for staking.
+///
+/// Rewards accrue at `reward_rate` tokens per unit of block time, shared among the stakers by
+/// their stake, for as long as the reserve lasts. The share of each staker is tracked with a
+/// cumulative reward per staked token, like the rewards of `deposit_rewards`.
+#[derive(ReadWriteState, CreateTypeSpec)]
+pub struct StakingLedger {
+    /// The staked tokens of each account.
+    pub stakes: SortedVecMap<Address, u128>,
+    /// The sum of all stakes.
+    pub total_staked: u128,
+    /// The rewards paid per unit of block time, shared among all stakers.
+    pub reward_rate: u128,
+    /// The time after unstaking before the tokens can be moved.
+    pub unbonding_period: i64,
+    /// Tokens funded by the owner that have not yet been paid as rewards.
+    pub reserve: u128,
+    /// Rewards that have accrued to stakers but have not yet been claimed.
+    pub accrued_rewards: u128,
+    /// The rewards paid per staked token, scaled by [`REWARD_PRECISION`].
+    pub reward_per_token: u128,
+    /// The part of the accrued rewards, scaled by [`REWARD_PRECISION`], that could not be divided
+    /// evenly and is carried over to the next update.
+    pub reward_remainder: u128,
+    /// The block time up to which rewards have accrued.
+    pub last_update: i64,
+    /// The value of `reward_per_token` when the rewards of each staker were last settled.
+    pub reward_paid_per_token: SortedVecMap<Address, u128>,
+    /// Settled rewards of each staker that have not yet been claimed.
+    pub unclaimed_rewards: SortedVecMap<Address, u128>,
+}
+
+impl StakingLedger {
+    /// Creates an empty staking ledger without rewards.
+    fn new() -> StakingLedger {
+        StakingLedger {
+            stakes: SortedVecMap::new(),
+            total_staked: 0,
+            reward_rate: 0,
+            unbonding_period: 0,
+            reserve: 0,
+            accrued_rewards: 0,
+            reward_per_token: 0,
+            reward_remainder: 0,
+            last_update: 0,
+            reward_paid_per_token: SortedVecMap::new(),
+            unclaimed_rewards: SortedVecMap::new(),
+        }
+    }
+
+    /// Gets the staked tokens of `staker`.
+    fn stake_of(&self, staker: Address) -> u128 {
+        *self.stakes.get(&staker).unwrap_or(&0)
+    }
+
+    /// Computes the rewards that accrue from `last_update` until `block_time`, together with the
+    /// resulting reward per token and remainder.
+    fn accrue(&self, block_time: i64) -> (u128, u128, u128) {
+        if self.total_staked == 0 || block_time <= self.last_update {
+            return (0, self.reward_per_token, self.reward_remainder);
+        }
+        let elapsed = (block_time - self.last_update) as u128;
+        let rewards = self.reward_rate.saturating_mul(elapsed).min(self.reserve);
+        let scaled_rewards = match rewards
+            .checked_mul(REWARD_PRECISION)
+            .and_then(|scaled| scaled.checked_add(self.reward_remainder))
+        {
+            Some(scaled_rewards) => scaled_rewards,
+            None => TokenError::Overflow.raise(),
+        };
+        let reward_per_token = match self
+            .reward_per_token
+            .checked_add(scaled_rewards / self.total_staked)
+        {
+            Some(reward_per_token) => reward_per_token,
+            None => TokenError::Overflow.raise(),
+        };
+        (
+            rewards,
+            reward_per_token,
+            scaled_rewards % self.total_staked,
+        )
+    }
+
+    /// Accrues the rewards until `block_time`, moving them from the reserve to the stakers.
+    fn update(&mut self, block_time: i64) {
+        let (rewards, reward_per_token, reward_remainder) = self.accrue(block_time);
+        self.reserve -= rewards;
+        self.accrued_rewards += rewards;
+        self.reward_per_token = reward_per_token;
+        self.reward_remainder = reward_remainder;
+        self.last_update = self.last_update.max(block_time);
+    }
+
+    /// Computes the rewards of `staker` that have not been claimed, given `reward_per_token`.
+    fn earned(&self, staker: Address, reward_per_token: u128) -> u128 {
+        let paid_per_token = *self.reward_paid_per_token.get(&staker).unwrap_or(&0);
+        let unclaimed = *self.unclaimed_rewards.get(&staker).unwrap_or(&0);
+        unclaimed + reward_for(self.stake_of(staker), reward_per_token - paid_per_token)
+    }
+
+    /// Moves the pending rewards of `staker` to its unclaimed rewards. Must be called after
+    /// `update`, and before the stake of `staker` changes.
+    fn settle(&mut self, staker: Address) {
+        let earned = self.earned(staker, self.reward_per_token);
+        if earned > 0 {
+            self.unclaimed_rewards.insert(staker, earned);
+        }
+        self.reward_paid_per_token
+            .insert(staker, self.reward_per_token);
+    }
+}
+
+/// Computes `amount * bps / BASIS_POINTS`, rounded down, without overflowing.
+fn basis_points_of(amount: u128, bps: u16) -> u128 {
+    let bps = bps as u128;
+    amount / BASIS_POINTS * bps + amount % BASIS_POINTS * bps / BASIS_POINTS
+}
+
+/// Computes `amount * reward_per_token / REWARD_PRECISION`, rounded down, without overflowing
+/// as long as the result can be represented.
+fn reward_for(amount: u128, reward_per_token: u128) -> u128 {
+    let (amount_high, amount_low) = (amount / REWARD_PRECISION, amount % REWARD_PRECISION);
+    let (reward_high, reward_low) = (
+        reward_per_token / REWARD_PRECISION,
+        reward_per_token % REWARD_PRECISION,
+    );
+    amount_high * reward_high * REWARD_PRECISION
+        + amount_high * reward_low
+        + amount_low * reward_high
+        + amount_low * reward_low / REWARD_PRECISION
+}
+
+/// Finds the value at `snapshot_id` in `checkpoints`, or `current` if the value has not changed
+/// since the snapshot.
+fn value_at(checkpoints: &[Checkpoint], snapshot_id: u64, current: u128) -> u128 {
+    match checkpoints
+        .iter()
+        .find(|checkpoint| checkpoint.snapshot_id >= snapshot_id)
+    {
+        Some(checkpoint) => checkpoint.value,
+        None => current,
+    }
+}
+
+/// Records `current` for the latest snapshot in `checkpoints`, unless it has already been recorded.
+fn record_checkpoint(checkpoints: &mut Vec<Checkpoint>, current_snapshot_id: u64, current: u128) {
+    let last_snapshot_id = match checkpoints.last() {
+        Some(checkpoint) => checkpoint.snapshot_id,
+        None => 0,
+    };
+    if last_snapshot_id < current_snapshot_id {
+        checkpoints.push(Checkpoint {
+            snapshot_id: current_snapshot_id,
+            value: current,
+        });
+    }
+}
+
+impl VestingSchedule {
+    /// Computes the amount of tokens that have vested at `block_time`.
+    fn vested_amount(&self, block_time: i64) -> u128 {
+        if self.revoked || block_time >= self.start + self.duration {
+            return self.total_amount;
+        }
+        if block_time < self.start + self.cliff {
+            return 0;
+        }
+        let elapsed = (block_time - self.start) as u128;
+        let duration = self.duration as u128;
+        // Computes total_amount * elapsed / duration without overflowing.
+        let quotient = self.total_amount / duration;
+        let remainder = self.total_amount % duration;
+        quotient * elapsed + remainder * elapsed / duration
+    }
 }
 
 impl TokenState {
@@ -65,11 +716,157 @@ impl TokenState {
     /// ### Returns:
     ///
     /// An [`u64`] representing the amount owned by the passed address.
-    pub fn balance_of(&mut self, owner: Address) -> u128 {
-        if !self.balances.contains_key(&owner) {
-            self.balances.insert(owner, 0);
+    pub fn balance_of(&self, owner: Address) -> u128 {
+        *self.balances.get(&owner).unwrap_or(&0)
+    }
+
+    /// Gets the balance of an account at a snapshot.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `owner`: [`Address`] The address to query the balance of.
+    ///
+    /// * `snapshot_id`: [`u64`] The id of the snapshot.
+    ///
+    /// ### Returns:
+    ///
+    /// The balance of `owner` when the snapshot was taken.
+    /// Throws if the snapshot does not exist.
+    pub fn balance_of_at(&self, owner: Address, snapshot_id: u64) -> u128 {
+        self.assert_snapshot_exists(snapshot_id);
+        match self.account_snapshots.get(&owner) {
+            Some(checkpoints) => value_at(checkpoints, snapshot_id, self.balance_of(owner)),
+            None => self.balance_of(owner),
+        }
+    }
+
+    /// Gets the total supply at a snapshot.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `snapshot_id`: [`u64`] The id of the snapshot.
+    ///
+    /// ### Returns:
+    ///
+    /// The total supply when the snapshot was taken.
+    /// Throws if the snapshot does not exist.
+    pub fn total_supply_at(&self, snapshot_id: u64) -> u128 {
+        self.assert_snapshot_exists(snapshot_id);
+        value_at(&self.total_supply_snapshots, snapshot_id, self.total_supply)
+    }
+
+    /// Throws if no snapshot with `snapshot_id` has been taken.
+    fn assert_snapshot_exists(&self, snapshot_id: u64) {
+        if snapshot_id == 0 || snapshot_id > self.current_snapshot_id {
+            TokenError::UnknownSnapshot.raise();
+        }
+    }
+
+    /// Gets the address that an account has delegated its voting power to.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `account`: [`Address`] The address to query the delegate of.
+    ///
+    /// ### Returns:
+    ///
+    /// The delegate of `account`, or `None` if it has not delegated its voting power.
+    pub fn delegates(&self, account: Address) -> Option<Address> {
+        self.delegates.get(&account).copied()
+    }
+
+    /// Gets the current voting power of a delegate.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `delegatee`: [`Address`] The address to query the votes of.
+    ///
+    /// ### Returns:
+    ///
+    /// The sum of the balances of the accounts that have delegated to `delegatee`.
+    pub fn get_votes(&self, delegatee: Address) -> u128 {
+        match self.vote_checkpoints.get(&delegatee) {
+            Some(checkpoints) => match checkpoints.last() {
+                Some(checkpoint) => checkpoint.votes,
+                None => 0,
+            },
+            None => 0,
+        }
+    }
+
+    /// Gets the voting power of a delegate at a past block time.
+    /// Throws if `block_time` is not strictly before `current_block_time`, as the votes at the
+    /// current block time can still change.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `delegatee`: [`Address`] The address to query the votes of.
+    ///
+    /// * `block_time`: [`i64`] The block time to query the votes at.
+    ///
+    /// * `current_block_time`: [`i64`] The current block time.
+    ///
+    /// ### Returns:
+    ///
+    /// The votes of `delegatee` at the end of `block_time`.
+    pub fn get_past_votes(
+        &self,
+        delegatee: Address,
+        block_time: i64,
+        current_block_time: i64,
+    ) -> u128 {
+        if block_time >= current_block_time {
+            TokenError::FutureLookup.raise();
+        }
+        match self.vote_checkpoints.get(&delegatee) {
+            Some(checkpoints) => match checkpoints
+                .iter()
+                .rev()
+                .find(|checkpoint| checkpoint.from_time <= block_time)
+            {
+                Some(checkpoint) => checkpoint.votes,
+                None => 0,
+            },
+            None => 0,
+        }
+    }
+
+    /// Gets the amount of tokens of an account that are locked.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `owner`: [`Address`] The address to query the locked balance of.
+    ///
+    /// * `block_time`: [`i64`] The block time to check the locks at.
+    ///
+    /// ### Returns:
+    ///
+    /// The amount of tokens of `owner` that cannot be moved at `block_time`.
+    pub fn locked_balance(&self, owner: Address, block_time: i64) -> u128 {
+        match self.locks.get(&owner) {
+            Some(locks) => locks
+                .iter()
+                .filter(|lock| lock.unlock_at > block_time)
+                .map(|lock| lock.amount)
+                .sum(),
+            None => 0,
         }
-        *self.balances.get(&owner).unwrap()
+    }
+
+    /// Gets the amount of tokens of an account that can be moved.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `owner`: [`Address`] The address to query the spendable balance of.
+    ///
+    /// * `block_time`: [`i64`] The block time to check the locks at.
+    ///
+    /// ### Returns:
+    ///
+    /// The balance of `owner` minus the tokens that are locked at `block_time`.
+    pub fn spendable_balance(&self, owner: Address, block_time: i64) -> u128 {
+        self.balance_of(owner)
+            .saturating_sub(self.locked_balance(owner, block_time))
     }
 
     /// Function to check the amount of tokens that an owner allowed to a spender.
@@ -80,87 +877,2701 @@ impl TokenState {
     ///
     /// * `spender`: [`Address`] The address which will spend the funds.
     ///
+    /// * `block_time`: [`i64`] The block time to check the allowance at.
+    ///
     /// ### Returns:
     ///
     /// A [`u64`] specifying the amount whicher `spender` is still allowed to withdraw from `owner`.
-    pub fn allowance(&mut self, owner: Address, spender: Address) -> u128 {
-        if !self.allowed.contains_key(&owner) {
-            self.allowed.insert(owner, SortedVecMap::new());
+    /// Allowances which have expired at `block_time` are reported as 0.
+    pub fn allowance(&self, owner: Address, spender: Address, block_time: i64) -> u128 {
+        match self.live_allowance(owner, spender, block_time) {
+            Some(allowance) => allowance.amount,
+            None => 0,
         }
-        let allowed_from_owner = self.allowed.get_mut(&owner).unwrap();
+    }
+
+    /// Function to check when the allowance from an owner to a spender expires.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `owner`: [`Address`] The address which owns the funds.
+    ///
+    /// * `spender`: [`Address`] The address which will spend the funds.
+    ///
+    /// * `block_time`: [`i64`] The block time to check the allowance at.
+    ///
+    /// ### Returns:
+    ///
+    /// The block time after which the allowance can no longer be used,
+    /// or `None` if the allowance does not expire or does not exist at `block_time`.
+    pub fn allowance_expiry(
+        &self,
+        owner: Address,
+        spender: Address,
+        block_time: i64,
+    ) -> Option<i64> {
+        match self.live_allowance(owner, spender, block_time) {
+            Some(allowance) => allowance.expires_at,
+            None => None,
+        }
+    }
 
-        if !allowed_from_owner.contains_key(&spender) {
-            allowed_from_owner.insert(spender, 0);
+    /// Checks whether `owner` has given `spender` an allowance that has expired at `block_time`.
+    fn has_expired_allowance(&self, owner: Address, spender: Address, block_time: i64) -> bool {
+        let allowance = self
+            .allowed
+            .get(&owner)
+            .and_then(|allowed_from_owner| allowed_from_owner.get(&spender));
+        match allowance {
+            Some(Allowance {
+                expires_at: Some(expires_at),
+                ..
+            }) => block_time > *expires_at,
+            _ => false,
         }
-        let allowance = allowed_from_owner.get(&spender).unwrap();
-        *allowance
     }
 
-    fn update_allowance(&mut self, owner: Address, spender: Address, amount: u128) {
-        if !self.allowed.contains_key(&owner) {
-            self.allowed.insert(owner, SortedVecMap::new());
+    /// Gets the allowance from `owner` to `spender`, unless it does not exist or has expired at
+    /// `block_time`.
+    fn live_allowance(
+        &self,
+        owner: Address,
+        spender: Address,
+        block_time: i64,
+    ) -> Option<&Allowance> {
+        let allowance = self.allowed.get(&owner)?.get(&spender)?;
+        match allowance.expires_at {
+            Some(expires_at) if block_time > expires_at => None,
+            _ => Some(allowance),
         }
-        let allowed_from_owner = self.allowed.get_mut(&owner).unwrap();
+    }
+
+    /// Gets the amount of tokens that have vested under a vesting schedule.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `vesting_id`: [`u64`] The id of the vesting schedule.
+    ///
+    /// * `block_time`: [`i64`] The block time to compute the vested amount at.
+    ///
+    /// ### Returns:
+    ///
+    /// The amount of tokens that have vested at `block_time`, including the tokens that have
+    /// already been released, or 0 if the schedule does not exist.
+    pub fn vested_amount(&self, vesting_id: u64, block_time: i64) -> u128 {
+        match self.vesting_schedules.get(&vesting_id) {
+            Some(schedule) => schedule.vested_amount(block_time),
+            None => 0,
+        }
+    }
+
+    /// Gets the amount of tokens the beneficiary of a vesting schedule can currently release.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `vesting_id`: [`u64`] The id of the vesting schedule.
+    ///
+    /// * `block_time`: [`i64`] The block time to compute the releasable amount at.
+    ///
+    /// ### Returns:
+    ///
+    /// The amount of tokens that have vested at `block_time` but have not yet been released,
+    /// or 0 if the schedule does not exist.
+    pub fn releasable_amount(&self, vesting_id: u64, block_time: i64) -> u128 {
+        match self.vesting_schedules.get(&vesting_id) {
+            Some(schedule) => schedule.vested_amount(block_time) - schedule.released_amount,
+            None => 0,
+        }
+    }
+
+    /// Function to check whether an account is exempt from transfer fees.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `account`: [`Address`] The address to check.
+    ///
+    /// ### Returns:
+    ///
+    /// `true` if `account` is the owner or has been added to the fee exemptions.
+    pub fn is_fee_exempt(&self, account: Address) -> bool {
+        self.owner == Some(account) || self.fee_exempt.contains(&account)
+    }
+
+    /// Computes the fee taken when transferring tokens.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `from`: [`Address`] The address the tokens are transferred from.
+    ///
+    /// * `to`: [`Address`] The address the tokens are transferred to.
+    ///
+    /// * `amount`: [`u128`] The amount to transfer.
+    ///
+    /// ### Returns:
+    ///
+    /// The part of `amount` paid to the fee recipient, rounded down. The fee is 0 if no fee is
+    /// configured, if either account is exempt, or if the recipient cannot currently receive
+    /// tokens, such that transfers never fail because of the fee recipient.
+    pub fn transfer_fee(&self, from: Address, to: Address, amount: u128) -> u128 {
+        match &self.fee_config {
+            Some(fee_config)
+                if !self.is_fee_exempt(from)
+                    && !self.is_fee_exempt(to)
+                    && !self.is_frozen(fee_config.recipient)
+                    && self.can_receive(fee_config.recipient) =>
+            {
+                basis_points_of(amount, fee_config.rate_bps)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Function to check whether an account is the recipient of the transfer fee.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `account`: [`Address`] The address to check.
+    ///
+    /// ### Returns:
+    ///
+    /// `true` if a fee is configured and `account` is its recipient.
+    pub fn is_fee_recipient(&self, account: Address) -> bool {
+        match &self.fee_config {
+            Some(fee_config) => fee_config.recipient == account,
+            None => false,
+        }
+    }
+
+    /// Gets the amount of tokens staked by an account.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `owner`: [`Address`] The address to query the stake of.
+    ///
+    /// ### Returns:
+    ///
+    /// The amount of tokens `owner` has staked.
+    pub fn staked_balance(&self, owner: Address) -> u128 {
+        self.staking.stake_of(owner)
+    }
+
+    /// Gets the staking rewards an account can claim at a block time.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `owner`: [`Address`] The address to query the staking rewards of.
+    ///
+    /// * `block_time`: [`i64`] The block time to compute the rewards at.
+    ///
+    /// ### Returns:
+    ///
+    /// The staking rewards of `owner` that have accrued at `block_time` and have not yet been
+    /// claimed.
+    pub fn staking_rewards(&self, owner: Address, block_time: i64) -> u128 {
+        let (_, reward_per_token, _) = self.staking.accrue(block_time);
+        self.staking.earned(owner, reward_per_token)
+    }
+
+    /// Gets the rewards an account can currently claim.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `owner`: [`Address`] The address to query the rewards of.
+    ///
+    /// ### Returns:
+    ///
+    /// The rewards of `owner` that have not yet been claimed.
+    pub fn claimable_rewards(&self, owner: Address) -> u128 {
+        let unclaimed = *self.unclaimed_rewards.get(&owner).unwrap_or(&0);
+        unclaimed + self.pending_rewards(owner)
+    }
+
+    /// Computes the rewards of `owner` since they were last settled.
+    fn pending_rewards(&self, owner: Address) -> u128 {
+        let paid_per_token = *self.reward_paid_per_token.get(&owner).unwrap_or(&0);
+        reward_for(
+            self.balance_of(owner),
+            self.reward_per_token - paid_per_token,
+        )
+    }
+
+    /// Computes the amount of tokens held in balances, i.e. the total supply except the tokens
+    /// held by vesting schedules, the reward pool, the staking ledger and pending transfers.
+    fn circulating_supply(&self) -> u128 {
+        let held_by_vesting: u128 = self
+            .vesting_schedules
+            .iter()
+            .map(|(_, schedule)| schedule.total_amount - schedule.released_amount)
+            .sum();
+        self.total_supply
+            - held_by_vesting
+            - self.reward_pool
+            - self.staking.total_staked
+            - self.staking.reserve
+            - self.staking.accrued_rewards
+            - self.pending_transfers
+    }
+
+    /// Moves the pending rewards of `owner` to its unclaimed rewards. Must be called before the
+    /// balance of `owner` changes.
+    fn settle_rewards(&mut self, owner: Address) {
+        let pending = self.pending_rewards(owner);
+        if pending > 0 {
+            let unclaimed = *self.unclaimed_rewards.get(&owner).unwrap_or(&0);
+            self.unclaimed_rewards.insert(owner, unclaimed + pending);
+        }
+        self.reward_paid_per_token
+            .insert(owner, self.reward_per_token);
+    }
+
+    /// Checks that the balances of all accounts, together with the tokens held by vesting
+    /// schedules, the reward pool and the staking ledger, add up to the total supply.
+    /// Every action of the contract preserves this invariant.
+    ///
+    /// ### Returns:
+    ///
+    /// `true` if the sum of all balances, unreleased vesting tokens, unclaimed rewards, staked
+    /// tokens and the staking reserve is equal to `total_supply`.
+    pub fn is_supply_consistent(&self) -> bool {
+        let mut sum: u128 = 0;
+        let held = self
+            .vesting_schedules
+            .iter()
+            .map(|(_, schedule)| schedule.total_amount - schedule.released_amount);
+        for amount in self
+            .balances
+            .iter()
+            .map(|(_, balance)| *balance)
+            .chain(held)
+            .chain([
+                self.reward_pool,
+                self.staking.total_staked,
+                self.staking.reserve,
+                self.staking.accrued_rewards,
+                self.pending_transfers,
+            ])
+        {
+            match sum.checked_add(amount) {
+                Some(new_sum) => sum = new_sum,
+                None => return false,
+            }
+        }
+        sum == self.total_supply
+    }
+
+    /// Function to check whether an address has been granted a role.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `role`: [`u8`] The role to check, e.g. [`ROLE_MINTER`].
+    ///
+    /// * `account`: [`Address`] The address to check.
+    ///
+    /// ### Returns:
+    ///
+    /// `true` if `account` is a member of `role`.
+    pub fn has_role(&self, role: u8, account: Address) -> bool {
+        match self.roles.get(&role) {
+            Some(role_data) => role_data.members.contains(&account),
+            None => false,
+        }
+    }
+
+    /// Gets the role whose members can grant and revoke `role`.
+    pub fn role_admin(&self, role: u8) -> u8 {
+        match self.roles.get(&role) {
+            Some(role_data) => role_data.admin_role,
+            None => ROLE_ADMIN,
+        }
+    }
+
+    /// Throws if `sender` is not a member of `role`.
+    fn assert_role(&self, role: u8, sender: Address) {
+        if !self.has_role(role, sender) {
+            TokenError::Unauthorized.raise();
+        }
+    }
+
+    /// Adds `account` to the members of `role`.
+    fn add_role_member(&mut self, role: u8, account: Address) {
+        if !self.roles.contains_key(&role) {
+            self.roles.insert(
+                role,
+                RoleData {
+                    admin_role: ROLE_ADMIN,
+                    members: vec![],
+                },
+            );
+        }
+        let role_data = self.roles.get_mut(&role).unwrap();
+        if !role_data.members.contains(&account) {
+            role_data.members.push(account);
+        }
+    }
+
+    /// Removes `account` from the members of `role`.
+    fn remove_role_member(&mut self, role: u8, account: Address) {
+        if let Some(role_data) = self.roles.get_mut(&role) {
+            role_data.members.retain(|member| *member != account);
+        }
+    }
+
+    /// Moves every role held by `from` to `to`, or revokes them if `to` is `None`.
+    fn move_roles(&mut self, from: Address, to: Option<Address>) {
+        let held: Vec<u8> = self
+            .roles
+            .iter()
+            .filter(|(_, role_data)| role_data.members.contains(&from))
+            .map(|(role, _)| *role)
+            .collect();
+        for role in held {
+            self.remove_role_member(role, from);
+            if let Some(to) = to {
+                self.add_role_member(role, to);
+            }
+        }
+    }
+
+    /// Throws if `sender` is not the owner of the contract.
+    /// After ownership has been renounced, this always throws.
+    fn assert_owner(&self, sender: Address) {
+        if self.owner != Some(sender) {
+            TokenError::Unauthorized.raise();
+        }
+    }
+
+    /// Function to check whether an account has been frozen.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `account`: [`Address`] The address to check.
+    ///
+    /// ### Returns:
+    ///
+    /// `true` if `account` can neither send nor receive tokens.
+    pub fn is_frozen(&self, account: Address) -> bool {
+        self.frozen_accounts.contains(&account)
+    }
+
+    /// Function to check whether an account is on the allowlist.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `account`: [`Address`] The address to check.
+    ///
+    /// ### Returns:
+    ///
+    /// `true` if `account` has been added to the allowlist.
+    pub fn is_allowlisted(&self, account: Address) -> bool {
+        self.allowlist.contains(&account)
+    }
+
+    /// Checks whether `to` can receive tokens, i.e. allowlist mode is disabled, or `to` is
+    /// allowlisted or the owner. Tokens can always be sent back to the owner, even by accounts
+    /// that have been removed from the allowlist.
+    fn can_receive(&self, to: Address) -> bool {
+        !self.allowlist_enabled || self.is_allowlisted(to) || self.owner == Some(to)
+    }
+
+    /// Throws if allowlist mode is enabled and `to` is neither allowlisted nor the owner.
+    fn assert_can_receive(&self, to: Address) {
+        if !self.can_receive(to) {
+            TokenError::NotAllowlisted.raise();
+        }
+    }
+
+    /// Throws if `account` has been frozen.
+    fn assert_not_frozen(&self, account: Address) {
+        if self.is_frozen(account) {
+            TokenError::AccountFrozen.raise();
+        }
+    }
+
+    /// Throws if `owner` has enough tokens to move `amount`, but some of them are locked at
+    /// `block_time`.
+    fn assert_unlocked(&self, owner: Address, amount: u128, block_time: i64) {
+        if amount <= self.balance_of(owner) && amount > self.spendable_balance(owner, block_time) {
+            TokenError::TokensLocked.raise();
+        }
+    }
+
+    /// Locks `amount` of the tokens of `owner` until `unlock_at`.
+    fn add_lock(&mut self, owner: Address, amount: u128, unlock_at: i64) {
+        if !self.locks.contains_key(&owner) {
+            self.locks.insert(owner, vec![]);
+        }
+        self.locks
+            .get_mut(&owner)
+            .unwrap()
+            .push(TokenLock { amount, unlock_at });
+    }
+
+    /// Removes the locks of `owner` that have expired at `block_time`.
+    fn remove_expired_locks(&mut self, owner: Address, block_time: i64) {
+        if let Some(locks) = self.locks.get_mut(&owner) {
+            locks.retain(|lock| lock.unlock_at > block_time);
+            if locks.is_empty() {
+                self.locks.remove(&owner);
+            }
+        }
+    }
+
+    /// Throws if the contract is paused.
+    fn assert_not_paused(&self) {
+        if self.paused {
+            TokenError::Paused.raise();
+        }
+    }
+
+    /// Sets the balance of `owner` to `amount`, recording the old balance for the latest snapshot
+    /// and settling the rewards earned with the old balance.
+    /// Accounts with a balance of 0 are removed from the ledger.
+    fn set_balance(&mut self, owner: Address, amount: u128) {
+        self.settle_rewards(owner);
+        if self.current_snapshot_id > 0 {
+            let current = self.balance_of(owner);
+            if !self.account_snapshots.contains_key(&owner) {
+                self.account_snapshots.insert(owner, vec![]);
+            }
+            let checkpoints = self.account_snapshots.get_mut(&owner).unwrap();
+            record_checkpoint(checkpoints, self.current_snapshot_id, current);
+        }
+        if amount == 0 {
+            self.balances.remove(&owner);
+            self.reward_paid_per_token.remove(&owner);
+        } else {
+            self.balances.insert(owner, amount);
+        }
+    }
+
+    /// Sets the total supply to `amount`, recording the old total supply for the latest snapshot.
+    fn set_total_supply(&mut self, amount: u128) {
+        if self.current_snapshot_id > 0 {
+            record_checkpoint(
+                &mut self.total_supply_snapshots,
+                self.current_snapshot_id,
+                self.total_supply,
+            );
+        }
+        self.total_supply = amount;
+    }
+
+    /// Subtracts `amount` from the balance of `from`, and from the votes of its delegate at
+    /// `block_time`.
+    /// Throws if `from` does not have enough tokens.
+    fn debit(&mut self, from: Address, amount: u128, block_time: i64) {
+        let from_amount = self.balance_of(from);
+        let o_new_from_amount = from_amount.checked_sub(amount);
+        match o_new_from_amount {
+            Some(new_from_amount) => {
+                self.set_balance(from, new_from_amount);
+                if let Some(delegatee) = self.delegates(from) {
+                    let votes = self.get_votes(delegatee) - amount;
+                    self.write_votes(delegatee, votes, block_time);
+                }
+            }
+            None => {
+                TokenError::InsufficientBalance.raise();
+            }
+        }
+    }
+
+    /// Adds `amount` to the balance of `to`, and to the votes of its delegate at `block_time`.
+    /// Throws if the new balance cannot be represented.
+    fn credit(&mut self, to: Address, amount: u128, block_time: i64) {
+        let to_amount = self.balance_of(to);
+        let o_new_to_amount = to_amount.checked_add(amount);
+        match o_new_to_amount {
+            Some(new_to_amount) => {
+                self.set_balance(to, new_to_amount);
+                if let Some(delegatee) = self.delegates(to) {
+                    let votes = self.get_votes(delegatee) + amount;
+                    self.write_votes(delegatee, votes, block_time);
+                }
+            }
+            None => {
+                TokenError::Overflow.raise();
+            }
+        }
+    }
+
+    /// Sets the votes of `delegatee` to `votes` from `block_time`.
+    /// Multiple changes at the same block time are merged into a single checkpoint.
+    fn write_votes(&mut self, delegatee: Address, votes: u128, block_time: i64) {
+        if !self.vote_checkpoints.contains_key(&delegatee) {
+            self.vote_checkpoints.insert(delegatee, vec![]);
+        }
+        let checkpoints = self.vote_checkpoints.get_mut(&delegatee).unwrap();
+        match checkpoints.last_mut() {
+            Some(last) if last.from_time == block_time => last.votes = votes,
+            _ => checkpoints.push(VoteCheckpoint {
+                from_time: block_time,
+                votes,
+            }),
+        }
+    }
+
+    /// Sets the allowance from `owner` to `spender` to `amount`, keeping the expiry of the
+    /// current allowance unless it has expired at `block_time`.
+    fn update_allowance(
+        &mut self,
+        owner: Address,
+        spender: Address,
+        amount: u128,
+        block_time: i64,
+    ) {
+        let expires_at = self.allowance_expiry(owner, spender, block_time);
+        self.set_allowance(owner, spender, amount, expires_at);
+    }
+
+    /// Sets the allowance from `owner` to `spender` to `amount`, expiring after `expires_at`.
+    /// Allowances of 0 are removed from state.
+    fn set_allowance(
+        &mut self,
+        owner: Address,
+        spender: Address,
+        amount: u128,
+        expires_at: Option<i64>,
+    ) {
+        if amount == 0 {
+            if let Some(allowed_from_owner) = self.allowed.get_mut(&owner) {
+                allowed_from_owner.remove(&spender);
+                if allowed_from_owner.is_empty() {
+                    self.allowed.remove(&owner);
+                }
+            }
+            return;
+        }
+        if !self.allowed.contains_key(&owner) {
+            self.allowed.insert(owner, SortedVecMap::new());
+        }
+        let allowed_from_owner = self.allowed.get_mut(&owner).unwrap();
+
+        allowed_from_owner.insert(spender, Allowance { amount, expires_at });
+    }
+
+    /// Decreases the allowance from `owner` to `spender` by `amount`.
+    /// Throws if `spender` has not been approved enough tokens, or if the allowance has expired
+    /// at `block_time`.
+    fn spend_allowance(&mut self, owner: Address, spender: Address, amount: u128, block_time: i64) {
+        let from_allowed = self.allowance(owner, spender, block_time);
+        let o_new_allowed_amount = from_allowed.checked_sub(amount);
+        match o_new_allowed_amount {
+            Some(new_allowed_amount) => {
+                self.update_allowance(owner, spender, new_allowed_amount, block_time);
+            }
+            None => {
+                TokenError::InsufficientAllowance.raise();
+            }
+        }
+    }
+}
+
+/// Initial function to bootstrap the contracts state. Must return the state-struct.
+///
+/// ### Parameters:
+///
+/// * `ctx`: [`ContractContext`], initial context.
+///
+/// * `name`: [`String`], the name of the token - e.g. "MyToken".\
+///
+/// * `symbol`: [`String`], the symbol of the token. E.g. "HIX".\
+///
+/// * `decimals`: [`u8`], the number of decimals the token uses - e.g. 8,
+/// means to divide the token amount by `100000000` to get its user representation.\
+///
+/// * `total_supply`: [`u128`], current amount of tokens for the TokenContract.
+///
+/// * `max_supply`: [`Option<u128>`], the maximum amount of tokens that can ever exist.
+///   `None` means that the owner can mint without a cap.
+///
+/// * `allowlist_enabled`: [`bool`], whether only allowlisted accounts can receive tokens.
+///
+/// * `voting_period`: [`i64`], the length of the voting window of proposals, in block time.
+///   Must be positive.
+///
+/// ### Returns:
+///
+/// The new state object of type [`TokenContractState`] with an initialized ledger.
+#[init]
+#[allow(clippy::too_many_arguments)]
+pub fn initialize(
+    ctx: ContractContext,
+    name: String,
+    symbol: String,
+    decimals: u8,
+    total_supply: u128,
+    max_supply: Option<u128>,
+    allowlist_enabled: bool,
+    voting_period: i64,
+) -> (TokenState, Vec<EventGroup>) {
+    if let Some(cap) = max_supply {
+        if total_supply > cap {
+            TokenError::MaxSupplyExceeded.raise();
+        }
+    }
+    if voting_period <= 0 {
+        TokenError::InvalidGovernanceParameters.raise();
+    }
+    let mut balances = SortedVecMap::new();
+    balances.insert(ctx.sender, total_supply);
+
+    let mut roles = SortedVecMap::new();
+    for role in [
+        ROLE_ADMIN,
+        ROLE_MINTER,
+        ROLE_PAUSER,
+        ROLE_FREEZER,
+        ROLE_KYC,
+        ROLE_SNAPSHOT,
+    ] {
+        roles.insert(
+            role,
+            RoleData {
+                admin_role: ROLE_ADMIN,
+                members: vec![ctx.sender],
+            },
+        );
+    }
+
+    let state = TokenState {
+        name,
+        symbol,
+        decimals,
+        owner: Some(ctx.sender),
+        pending_owner: None,
+        total_supply,
+        max_supply,
+        paused: false,
+        paused_by_governance: false,
+        roles,
+        frozen_accounts: vec![],
+        allowlist_enabled,
+        allowlist: vec![],
+        vesting_schedules: SortedVecMap::new(),
+        next_vesting_id: 0,
+        locks: SortedVecMap::new(),
+        current_snapshot_id: 0,
+        account_snapshots: SortedVecMap::new(),
+        total_supply_snapshots: vec![],
+        delegates: SortedVecMap::new(),
+        vote_checkpoints: SortedVecMap::new(),
+        voting_period,
+        quorum_bps: DEFAULT_QUORUM_BPS,
+        proposal_threshold_bps: DEFAULT_PROPOSAL_THRESHOLD_BPS,
+        proposals: SortedVecMap::new(),
+        next_proposal_id: 0,
+        reward_pool: 0,
+        reward_per_token: 0,
+        reward_remainder: 0,
+        reward_paid_per_token: SortedVecMap::new(),
+        unclaimed_rewards: SortedVecMap::new(),
+        staking: StakingLedger::new(),
+        fee_config: None,
+        fee_exempt: vec![],
+        pending_transfers: 0,
+        balances,
+        allowed: SortedVecMap::new(),
+    };
+
+    (state, vec![])
+}
+
+/// Represents the type of a transfer.
+#[derive(ReadWriteRPC, CreateTypeSpec)]
+pub struct Transfer {
+    /// The address to transfer to.
+    pub to: Address,
+    /// The amount to transfer.
+    pub amount: u128,
+}
+
+/// Notification emitted whenever tokens are moved from one account to another.
+#[derive(ReadWriteRPC, CreateTypeSpec)]
+pub struct TransferEvent {
+    /// The address the tokens were moved from.
+    pub from: Address,
+    /// The address the tokens were moved to.
+    pub to: Address,
+    /// The address that initiated the transfer. Equal to `from` unless an allowance was used.
+    pub spender: Address,
+    /// The amount of tokens moved.
+    pub amount: u128,
+}
+
+/// Notification emitted whenever an allowance is set.
+#[derive(ReadWriteRPC, CreateTypeSpec)]
+pub struct ApprovalEvent {
+    /// The address which owns the funds.
+    pub owner: Address,
+    /// The address which is allowed to spend the funds.
+    pub spender: Address,
+    /// The new allowance of `spender`.
+    pub amount: u128,
+    /// The block time after which the allowance expires, if any.
+    pub expires_at: Option<i64>,
+}
+
+/// Transfers `amount` of tokens to address `to` from the caller.
+/// The function throws if the message caller's account
+/// balance does not have enough tokens to spend.
+/// If the sender's account goes to 0, the sender's address is removed from state.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `to`: [`Address`], the address to transfer to.
+///
+/// * `amount`: [`u128`], amount to transfer.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger,
+/// and an event group containing the [`TransferEvent`].
+#[action(shortname = 0x01)]
+pub fn transfer(
+    context: ContractContext,
+    state: TokenState,
+    to: Address,
+    amount: u128,
+) -> (TokenState, Vec<EventGroup>) {
+    let (new_state, _) = core_transfer(context.sender, state, to, amount, context.block_time);
+    let mut event_group = EventGroup::builder();
+    add_transfer_events(
+        &mut event_group,
+        context.contract_address,
+        &new_state,
+        context.sender,
+        to,
+        context.sender,
+        amount,
+    );
+    (new_state, vec![event_group.build()])
+}
+
+/// Transfers a bulk of `amount` of tokens to address `to` from the caller.
+/// The function throws if the message caller's account
+/// balance does not have enough tokens to spend.
+/// If the sender's account goes to 0, the sender's address is removed from state.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `transfers`: [`Vec[Transfer]`], vector of [the address to transfer to, amount to transfer].
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger,
+/// and an event group containing a [`TransferEvent`] for each transfer.
+#[action(shortname = 0x02)]
+pub fn bulk_transfer(
+    context: ContractContext,
+    state: TokenState,
+    transfers: Vec<Transfer>,
+) -> (TokenState, Vec<EventGroup>) {
+    let mut new_state = state;
+    let mut event_group = EventGroup::builder();
+    for t in transfers {
+        new_state = core_transfer(
+            context.sender,
+            new_state,
+            t.to,
+            t.amount,
+            context.block_time,
+        )
+        .0;
+        add_transfer_events(
+            &mut event_group,
+            context.contract_address,
+            &new_state,
+            context.sender,
+            t.to,
+            context.sender,
+            t.amount,
+        );
+    }
+    (new_state, vec![event_group.build()])
+}
+
+/// Transfers `amount` of tokens from address `from` to address `to`.\
+/// This requires that the sender is allowed to do the transfer by the `from`
+/// account through the `approve` action.
+/// The function throws if the message caller's account
+/// balance does not have enough tokens to spend, or if the tokens were not approved.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `from`: [`Address`], the address to transfer from.
+///
+/// * `to`: [`Address`], the address to transfer to.
+///
+/// * `amount`: [`u128`], amount to transfer.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger,
+/// and an event group containing the [`TransferEvent`] and the [`ApprovalEvent`] of the
+/// remaining allowance.
+#[action(shortname = 0x03)]
+pub fn transfer_from(
+    context: ContractContext,
+    state: TokenState,
+    from: Address,
+    to: Address,
+    amount: u128,
+) -> (TokenState, Vec<EventGroup>) {
+    let (new_state, _) =
+        core_transfer_from(context.sender, state, from, to, amount, context.block_time);
+    let mut event_group = EventGroup::builder();
+    add_transfer_events(
+        &mut event_group,
+        context.contract_address,
+        &new_state,
+        from,
+        to,
+        context.sender,
+        amount,
+    );
+    add_spent_allowance_event(
+        &mut event_group,
+        context.contract_address,
+        &new_state,
+        from,
+        context.sender,
+        context.block_time,
+    );
+    (new_state, vec![event_group.build()])
+}
+
+/// Transfers a bulk of `amount` of tokens to address `to` from address `from` .\
+/// This requires that the sender is allowed to do the transfer by the `from`
+/// account through the `approve` action.
+/// The function throws if the message caller's account
+/// balance does not have enough tokens to spend, or if the tokens were not approved.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `from`: [`Address`], the address to transfer from.
+///
+/// * `transfers`: [`Vec[Transfer]`], vector of [the address to transfer to, amount to transfer].
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger,
+/// and an event group containing a [`TransferEvent`] for each transfer and the
+/// [`ApprovalEvent`] of the remaining allowance.
+#[action(shortname = 0x04)]
+pub fn bulk_transfer_from(
+    context: ContractContext,
+    state: TokenState,
+    from: Address,
+    transfers: Vec<Transfer>,
+) -> (TokenState, Vec<EventGroup>) {
+    let mut new_state = state;
+    let mut event_group = EventGroup::builder();
+    for t in transfers {
+        new_state = core_transfer_from(
+            context.sender,
+            new_state,
+            from,
+            t.to,
+            t.amount,
+            context.block_time,
+        )
+        .0;
+        add_transfer_events(
+            &mut event_group,
+            context.contract_address,
+            &new_state,
+            from,
+            t.to,
+            context.sender,
+            t.amount,
+        );
+    }
+    add_spent_allowance_event(
+        &mut event_group,
+        context.contract_address,
+        &new_state,
+        from,
+        context.sender,
+        context.block_time,
+    );
+    (new_state, vec![event_group.build()])
+}
+
+/// Allows `spender` to withdraw from the owners account multiple times, up to the `amount`.
+/// If this function is called again it overwrites the current allowance with `amount`.
+/// If `expires_at` is given, the allowance can no longer be used once the block time is past it.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `spender`: [`Address`], the address of the spender.
+///
+/// * `amount`: [`u128`], approved amount.
+///
+/// * `expires_at`: [`Option<i64>`], the block time after which the allowance expires, if any.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger,
+/// and an event group containing the [`ApprovalEvent`].
+#[action(shortname = 0x05)]
+pub fn approve(
+    context: ContractContext,
+    state: TokenState,
+    spender: Address,
+    amount: u128,
+    expires_at: Option<i64>,
+) -> (TokenState, Vec<EventGroup>) {
+    let mut new_state = state;
+    new_state.set_allowance(context.sender, spender, amount, expires_at);
+    let mut event_group = EventGroup::builder();
+    add_approval_event(
+        &mut event_group,
+        context.contract_address,
+        context.sender,
+        spender,
+        amount,
+        expires_at,
+    );
+    (new_state, vec![event_group.build()])
+}
+
+/// Creates `amount` new tokens and assigns them to address `to`, increasing the total supply.
+/// The function throws if the caller does not have the [`ROLE_MINTER`] role, if the contract is
+/// paused, if `to` is frozen or not allowlisted, or if the new total supply would exceed the
+/// maximum supply.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `to`: [`Address`], the address to mint tokens to.
+///
+/// * `amount`: [`u128`], amount to mint.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger,
+/// and an event group containing the [`TransferEvent`] from the contract.
+#[action(shortname = 0x06)]
+pub fn mint(
+    context: ContractContext,
+    state: TokenState,
+    to: Address,
+    amount: u128,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_role(ROLE_MINTER, context.sender);
+    let (new_state, _) = core_mint(state, to, amount, context.block_time);
+    let mut event_group = EventGroup::builder();
+    add_transfer_event(
+        &mut event_group,
+        context.contract_address,
+        context.contract_address,
+        to,
+        context.sender,
+        amount,
+    );
+    (new_state, vec![event_group.build()])
+}
+
+/// Destroys `amount` of tokens from the account of the caller, decreasing the total supply.
+/// The function throws if the message caller's account
+/// balance does not have enough tokens to burn.
+/// If the sender's account goes to 0, the sender's address is removed from state.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `amount`: [`u128`], amount to burn.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger,
+/// and an event group containing the [`TransferEvent`] to the contract.
+#[action(shortname = 0x07)]
+pub fn burn(
+    context: ContractContext,
+    state: TokenState,
+    amount: u128,
+) -> (TokenState, Vec<EventGroup>) {
+    let (new_state, _) = core_burn(context.sender, state, amount, context.block_time);
+    let mut event_group = EventGroup::builder();
+    add_transfer_event(
+        &mut event_group,
+        context.contract_address,
+        context.sender,
+        context.contract_address,
+        context.sender,
+        amount,
+    );
+    (new_state, vec![event_group.build()])
+}
+
+/// Destroys `amount` of tokens from address `from`, decreasing the total supply.\
+/// This requires that the sender is allowed to use the tokens of the `from`
+/// account through the `approve` action.
+/// The function throws if the `from` account does not have enough tokens to burn,
+/// or if the tokens were not approved.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `from`: [`Address`], the address to burn tokens from.
+///
+/// * `amount`: [`u128`], amount to burn.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger,
+/// and an event group containing the [`TransferEvent`] to the contract and the
+/// [`ApprovalEvent`] of the remaining allowance.
+#[action(shortname = 0x08)]
+pub fn burn_from(
+    context: ContractContext,
+    state: TokenState,
+    from: Address,
+    amount: u128,
+) -> (TokenState, Vec<EventGroup>) {
+    let (new_state, _) = core_burn_from(context.sender, state, from, amount, context.block_time);
+    let mut event_group = EventGroup::builder();
+    add_transfer_event(
+        &mut event_group,
+        context.contract_address,
+        from,
+        context.contract_address,
+        context.sender,
+        amount,
+    );
+    add_spent_allowance_event(
+        &mut event_group,
+        context.contract_address,
+        &new_state,
+        from,
+        context.sender,
+        context.block_time,
+    );
+    (new_state, vec![event_group.build()])
+}
+
+/// Transfers `amount` of tokens to the contract `to` from the caller, and notifies `to`
+/// by calling its action with the given `shortname`.
+/// The receiving action is called with the arguments `(sender: Address, amount: u128, data: Vec<u8>)`,
+/// where `amount` is the amount received after any transfer fee.
+/// Until the call has completed, the received tokens are held by the contract. They are credited
+/// to `to` if the call succeeds, and refunded to the caller if it fails, while the fee is kept.
+/// The function throws if `to` is not a contract, or if the message caller's account
+/// balance does not have enough tokens to spend.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `to`: [`Address`], the contract to transfer to.
+///
+/// * `amount`: [`u128`], amount to transfer.
+///
+/// * `shortname`: [`u32`], the shortname of the action to call on `to`.
+///
+/// * `data`: [`Vec<u8>`], opaque payload passed on to `to`.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger,
+/// an event group containing the [`TransferEvent`]s to the contract and to the fee recipient,
+/// and an event group calling `to` with a callback to `transfer_and_call_callback`.
+#[action(shortname = 0x09)]
+pub fn transfer_and_call(
+    context: ContractContext,
+    state: TokenState,
+    to: Address,
+    amount: u128,
+    shortname: u32,
+    data: Vec<u8>,
+) -> (TokenState, Vec<EventGroup>) {
+    if to.address_type == AddressType::Account {
+        TokenError::InvalidReceiver.raise();
+    }
+    let (mut new_state, received) =
+        withdraw_for_transfer(context.sender, state, to, amount, context.block_time);
+    new_state.pending_transfers += received;
+    let mut notifications = EventGroup::builder();
+    add_transfer_event(
+        &mut notifications,
+        context.contract_address,
+        context.sender,
+        context.contract_address,
+        context.sender,
+        received,
+    );
+    if let Some(fee_config) = &new_state.fee_config {
+        if amount > received {
+            add_transfer_event(
+                &mut notifications,
+                context.contract_address,
+                context.sender,
+                fee_config.recipient,
+                context.sender,
+                amount - received,
+            );
+        }
+    }
+    // The receiver is called in its own event group, so that the callback only sees its result.
+    let mut event_group = EventGroup::builder();
+    event_group
+        .call(to, Shortname::from_u32(shortname))
+        .argument(context.sender)
+        .argument(received)
+        .argument(data)
+        .done();
+    event_group
+        .with_callback(SHORTNAME_TRANSFER_AND_CALL_CALLBACK)
+        .argument(context.sender)
+        .argument(to)
+        .argument(received)
+        .done();
+    (new_state, vec![notifications.build(), event_group.build()])
+}
+
+/// Callback for `transfer_and_call`.
+/// Releases the tokens held for the transfer: they are credited to `to` if the receiving
+/// contract accepted the call, and refunded to `from` if it rejected it. Only the result of the
+/// call to `to` is considered, so the outcome of the event notifications cannot trigger a
+/// refund. The tokens are released even if the contract has been paused in the meantime.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the callback.
+///
+/// * `callback_context`: [`CallbackContext`], the results of the call to the receiver.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `from`: [`Address`], the original sender of the tokens.
+///
+/// * `to`: [`Address`], the contract that was called.
+///
+/// * `amount`: [`u128`], amount held for `to`, after any transfer fee.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the held tokens released, and an
+/// event group containing the [`TransferEvent`] from the contract.
+#[callback(shortname = 0x10)]
+pub fn transfer_and_call_callback(
+    context: ContractContext,
+    callback_context: CallbackContext,
+    state: TokenState,
+    from: Address,
+    to: Address,
+    amount: u128,
+) -> (TokenState, Vec<EventGroup>) {
+    let accepted = callback_context
+        .results
+        .first()
+        .is_some_and(|result| result.succeeded);
+    let recipient = if accepted { to } else { from };
+    let mut new_state = state;
+    new_state.pending_transfers -= amount;
+    new_state.credit(recipient, amount, context.block_time);
+    let mut event_group = EventGroup::builder();
+    add_transfer_event(
+        &mut event_group,
+        context.contract_address,
+        context.contract_address,
+        recipient,
+        from,
+        amount,
+    );
+    (new_state, vec![event_group.build()])
+}
+
+/// Increases the allowance of `spender` to withdraw from the owners account by `amount`.
+/// Unlike `approve`, the change is relative to the current allowance, so it cannot be raced by
+/// a `transfer_from` of the spender. The expiry of the current allowance is kept.
+/// The function throws if the new allowance cannot be represented, or if the current allowance
+/// has expired, as it would otherwise be renewed without an expiry. An expired allowance can be
+/// renewed with `approve` or `safe_approve`.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `spender`: [`Address`], the address of the spender.
+///
+/// * `amount`: [`u128`], amount to add to the allowance.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger,
+/// and an event group containing the [`ApprovalEvent`].
+#[action(shortname = 0x0A)]
+pub fn increase_allowance(
+    context: ContractContext,
+    state: TokenState,
+    spender: Address,
+    amount: u128,
+) -> (TokenState, Vec<EventGroup>) {
+    if state.has_expired_allowance(context.sender, spender, context.block_time) {
+        TokenError::AllowanceExpired.raise();
+    }
+    let mut new_state = state;
+    let current = new_state.allowance(context.sender, spender, context.block_time);
+    let new_amount = match current.checked_add(amount) {
+        Some(new_amount) => new_amount,
+        None => TokenError::Overflow.raise(),
+    };
+    new_state.update_allowance(context.sender, spender, new_amount, context.block_time);
+    let mut event_group = EventGroup::builder();
+    add_approval_event(
+        &mut event_group,
+        context.contract_address,
+        context.sender,
+        spender,
+        new_amount,
+        new_state.allowance_expiry(context.sender, spender, context.block_time),
+    );
+    (new_state, vec![event_group.build()])
+}
+
+/// Decreases the allowance of `spender` to withdraw from the owners account by `amount`.
+/// Unlike `approve`, the change is relative to the current allowance, so it cannot be raced by
+/// a `transfer_from` of the spender. The expiry of the current allowance is kept.
+/// If `amount` is larger than the current allowance, the allowance is set to 0 when
+/// `clamp_to_zero` is set, otherwise the function throws.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `spender`: [`Address`], the address of the spender.
+///
+/// * `amount`: [`u128`], amount to subtract from the allowance.
+///
+/// * `clamp_to_zero`: [`bool`], whether to clamp the allowance to 0 instead of failing.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger,
+/// and an event group containing the [`ApprovalEvent`].
+#[action(shortname = 0x0B)]
+pub fn decrease_allowance(
+    context: ContractContext,
+    state: TokenState,
+    spender: Address,
+    amount: u128,
+    clamp_to_zero: bool,
+) -> (TokenState, Vec<EventGroup>) {
+    let mut new_state = state;
+    let current = new_state.allowance(context.sender, spender, context.block_time);
+    let new_amount = match current.checked_sub(amount) {
+        Some(new_amount) => new_amount,
+        None if clamp_to_zero => 0,
+        None => TokenError::InsufficientAllowance.raise(),
+    };
+    new_state.update_allowance(context.sender, spender, new_amount, context.block_time);
+    let mut event_group = EventGroup::builder();
+    add_approval_event(
+        &mut event_group,
+        context.contract_address,
+        context.sender,
+        spender,
+        new_amount,
+        new_state.allowance_expiry(context.sender, spender, context.block_time),
+    );
+    (new_state, vec![event_group.build()])
+}
+
+/// Allows `spender` to withdraw from the owners account multiple times, up to `new_amount`,
+/// but only if the current allowance of `spender` is `expected_current`.
+/// This allows rotating an allowance without racing a `transfer_from` of the spender.
+/// An expired allowance counts as 0.
+/// The function throws if the current allowance differs from `expected_current`.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `spender`: [`Address`], the address of the spender.
+///
+/// * `expected_current`: [`u128`], the allowance that `spender` is expected to have.
+///
+/// * `new_amount`: [`u128`], the new approved amount.
+///
+/// * `expires_at`: [`Option<i64>`], the last block time at which the allowance can be used, or
+///   `None` if it never expires.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger,
+/// and an event group containing the [`ApprovalEvent`].
+#[action(shortname = 0x0C)]
+pub fn safe_approve(
+    context: ContractContext,
+    state: TokenState,
+    spender: Address,
+    expected_current: u128,
+    new_amount: u128,
+    expires_at: Option<i64>,
+) -> (TokenState, Vec<EventGroup>) {
+    if state.allowance(context.sender, spender, context.block_time) != expected_current {
+        TokenError::AllowanceMismatch.raise();
+    }
+    let mut new_state = state;
+    new_state.set_allowance(context.sender, spender, new_amount, expires_at);
+    let mut event_group = EventGroup::builder();
+    add_approval_event(
+        &mut event_group,
+        context.contract_address,
+        context.sender,
+        spender,
+        new_amount,
+        expires_at,
+    );
+    (new_state, vec![event_group.build()])
+}
+
+/// Pauses the contract, halting all transfers, mints and burns until `unpause` is called.
+/// A pause started by a pauser cannot be ended by a governance proposal, even if the contract
+/// was already paused by one.
+/// The function throws if the caller does not have the [`ROLE_PAUSER`] role.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the contract paused.
+#[action(shortname = 0x0D)]
+pub fn pause(context: ContractContext, state: TokenState) -> (TokenState, Vec<EventGroup>) {
+    state.assert_role(ROLE_PAUSER, context.sender);
+    let mut new_state = state;
+    new_state.paused = true;
+    new_state.paused_by_governance = false;
+    (new_state, vec![])
+}
+
+/// Unpauses the contract, allowing transfers and burns again.
+/// The function throws if the caller does not have the [`ROLE_PAUSER`] role.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the contract unpaused.
+#[action(shortname = 0x0E)]
+pub fn unpause(context: ContractContext, state: TokenState) -> (TokenState, Vec<EventGroup>) {
+    state.assert_role(ROLE_PAUSER, context.sender);
+    let mut new_state = state;
+    new_state.paused = false;
+    new_state.paused_by_governance = false;
+    (new_state, vec![])
+}
+
+/// Proposes `new_owner` as the next owner of the contract.
+/// Ownership is only transferred once `new_owner` calls `accept_ownership`.
+/// A new proposal replaces any previous proposal.
+/// The function throws if the caller is not the owner of the contract.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `new_owner`: [`Address`], the address proposed as the next owner.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the pending owner set.
+#[action(shortname = 0x0F)]
+pub fn propose_owner(
+    context: ContractContext,
+    state: TokenState,
+    new_owner: Address,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_owner(context.sender);
+    let mut new_state = state;
+    new_state.pending_owner = Some(new_owner);
+    (new_state, vec![])
+}
+
+/// Accepts the ownership of the contract, completing a proposal made with `propose_owner`.
+/// Every role held by the previous owner is moved to the caller.
+/// The function throws if the caller is not the pending owner.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the caller as owner and holder of
+/// the previous owner's roles.
+#[action(shortname = 0x11)]
+pub fn accept_ownership(
+    context: ContractContext,
+    state: TokenState,
+) -> (TokenState, Vec<EventGroup>) {
+    if state.pending_owner != Some(context.sender) {
+        TokenError::Unauthorized.raise();
+    }
+    let mut new_state = state;
+    if let Some(old_owner) = new_state.owner {
+        new_state.move_roles(old_owner, Some(context.sender));
+    }
+    new_state.owner = Some(context.sender);
+    new_state.pending_owner = None;
+    (new_state, vec![])
+}
+
+/// Renounces the ownership of the contract, together with every role held by the owner.
+/// Afterwards the contract has no owner, and every owner-only action is permanently disabled.
+/// Roles granted to other accounts are kept.
+/// The function throws if the caller is not the owner of the contract.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] without an owner, and with the roles of
+/// the previous owner revoked.
+#[action(shortname = 0x12)]
+pub fn renounce_ownership(
+    context: ContractContext,
+    state: TokenState,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_owner(context.sender);
+    let mut new_state = state;
+    new_state.move_roles(context.sender, None);
+    new_state.owner = None;
+    new_state.pending_owner = None;
+    (new_state, vec![])
+}
+
+/// Grants `role` to `account`.
+/// The function throws if the caller is not a member of the admin role of `role`.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `role`: [`u8`], the role to grant, e.g. [`ROLE_MINTER`].
+///
+/// * `account`: [`Address`], the address to grant the role to.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the role granted.
+#[action(shortname = 0x13)]
+pub fn grant_role(
+    context: ContractContext,
+    state: TokenState,
+    role: u8,
+    account: Address,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_role(state.role_admin(role), context.sender);
+    let mut new_state = state;
+    new_state.add_role_member(role, account);
+    (new_state, vec![])
+}
+
+/// Revokes `role` from `account`.
+/// The function throws if the caller is not a member of the admin role of `role`.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `role`: [`u8`], the role to revoke, e.g. [`ROLE_MINTER`].
+///
+/// * `account`: [`Address`], the address to revoke the role from.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the role revoked.
+#[action(shortname = 0x14)]
+pub fn revoke_role(
+    context: ContractContext,
+    state: TokenState,
+    role: u8,
+    account: Address,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_role(state.role_admin(role), context.sender);
+    let mut new_state = state;
+    new_state.remove_role_member(role, account);
+    (new_state, vec![])
+}
+
+/// Gives up `role` for the caller.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `role`: [`u8`], the role to give up, e.g. [`ROLE_MINTER`].
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] without the caller in `role`.
+#[action(shortname = 0x15)]
+pub fn renounce_role(
+    context: ContractContext,
+    state: TokenState,
+    role: u8,
+) -> (TokenState, Vec<EventGroup>) {
+    let mut new_state = state;
+    new_state.remove_role_member(role, context.sender);
+    (new_state, vec![])
+}
+
+/// Sets the role whose members can grant and revoke `role`.
+/// The function throws if the caller is not a member of the current admin role of `role`.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `role`: [`u8`], the role to change the admin role of.
+///
+/// * `admin_role`: [`u8`], the new admin role of `role`.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the admin role updated.
+#[action(shortname = 0x16)]
+pub fn set_role_admin(
+    context: ContractContext,
+    state: TokenState,
+    role: u8,
+    admin_role: u8,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_role(state.role_admin(role), context.sender);
+    let mut new_state = state;
+    match new_state.roles.get_mut(&role) {
+        Some(role_data) => role_data.admin_role = admin_role,
+        None => {
+            new_state.roles.insert(
+                role,
+                RoleData {
+                    admin_role,
+                    members: vec![],
+                },
+            );
+        }
+    }
+    (new_state, vec![])
+}
+
+/// Freezes `account`, such that it can neither send nor receive tokens,
+/// and its allowances can no longer be used.
+/// The function throws if the caller does not have the [`ROLE_FREEZER`] role, or if `account`
+/// is the recipient of the transfer fee, which must first be changed with `set_fee_config`.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `account`: [`Address`], the address to freeze.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the account frozen.
+#[action(shortname = 0x17)]
+pub fn freeze(
+    context: ContractContext,
+    state: TokenState,
+    account: Address,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_role(ROLE_FREEZER, context.sender);
+    if state.is_fee_recipient(account) {
+        TokenError::InvalidFeeConfig.raise();
+    }
+    let mut new_state = state;
+    if !new_state.is_frozen(account) {
+        new_state.frozen_accounts.push(account);
+    }
+    (new_state, vec![])
+}
+
+/// Unfreezes `account`, allowing it to send and receive tokens again.
+/// The function throws if the caller does not have the [`ROLE_FREEZER`] role.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `account`: [`Address`], the address to unfreeze.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the account unfrozen.
+#[action(shortname = 0x18)]
+pub fn unfreeze(
+    context: ContractContext,
+    state: TokenState,
+    account: Address,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_role(ROLE_FREEZER, context.sender);
+    let mut new_state = state;
+    new_state
+        .frozen_accounts
+        .retain(|frozen_account| *frozen_account != account);
+    (new_state, vec![])
+}
+
+/// Adds `accounts` to the allowlist, allowing them to receive tokens in allowlist mode.
+/// The function throws if the caller does not have the [`ROLE_KYC`] role.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `accounts`: [`Vec<Address>`], the addresses to add.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated allowlist.
+#[action(shortname = 0x19)]
+pub fn add_to_allowlist(
+    context: ContractContext,
+    state: TokenState,
+    accounts: Vec<Address>,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_role(ROLE_KYC, context.sender);
+    let mut new_state = state;
+    for account in accounts {
+        if !new_state.is_allowlisted(account) {
+            new_state.allowlist.push(account);
+        }
+    }
+    (new_state, vec![])
+}
+
+/// Removes `accounts` from the allowlist.
+/// Removed accounts keep their tokens, but can only send them back to the owner while
+/// allowlist mode is enabled.
+/// The function throws if the caller does not have the [`ROLE_KYC`] role, or if one of
+/// `accounts` is the recipient of the transfer fee, which must first be changed with
+/// `set_fee_config`.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `accounts`: [`Vec<Address>`], the addresses to remove.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated allowlist.
+#[action(shortname = 0x1A)]
+pub fn remove_from_allowlist(
+    context: ContractContext,
+    state: TokenState,
+    accounts: Vec<Address>,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_role(ROLE_KYC, context.sender);
+    if accounts
+        .iter()
+        .any(|account| state.is_fee_recipient(*account))
+    {
+        TokenError::InvalidFeeConfig.raise();
+    }
+    let mut new_state = state;
+    new_state
+        .allowlist
+        .retain(|account| !accounts.contains(account));
+    (new_state, vec![])
+}
+
+/// Creates a vesting schedule, moving `amount` of tokens from the caller into the schedule.
+/// The tokens vest linearly from `start` until `start + duration`, but nothing can be released
+/// before `start + cliff`. The new schedule is given the id `next_vesting_id`.
+/// The function throws if the caller is not the owner, if the owner does not have enough tokens,
+/// if the contract is paused, if either account is frozen, if `beneficiary` is not allowed to
+/// hold tokens in allowlist mode, or if `cliff` is not between 0 and `duration`.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `beneficiary`: [`Address`], the address that can release the vested tokens.
+///
+/// * `amount`: [`u128`], amount of tokens to vest.
+///
+/// * `start`: [`i64`], the block time at which vesting starts.
+///
+/// * `cliff`: [`i64`], the time after `start` before which nothing can be released.
+///
+/// * `duration`: [`i64`], the time after `start` at which all tokens have vested.
+///
+/// * `revocable`: [`bool`], whether the owner can revoke the unvested tokens.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the new vesting schedule,
+/// and an event group containing the [`TransferEvent`] to the contract.
+#[action(shortname = 0x1B)]
+#[allow(clippy::too_many_arguments)]
+pub fn create_vesting(
+    context: ContractContext,
+    state: TokenState,
+    beneficiary: Address,
+    amount: u128,
+    start: i64,
+    cliff: i64,
+    duration: i64,
+    revocable: bool,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_owner(context.sender);
+    state.assert_not_paused();
+    state.assert_not_frozen(context.sender);
+    state.assert_not_frozen(beneficiary);
+    state.assert_can_receive(beneficiary);
+    state.assert_unlocked(context.sender, amount, context.block_time);
+    if cliff < 0 || cliff > duration || start.checked_add(duration).is_none() {
+        TokenError::InvalidVestingSchedule.raise();
+    }
+    let mut new_state = state;
+    new_state.debit(context.sender, amount, context.block_time);
+    let vesting_id = new_state.next_vesting_id;
+    new_state.vesting_schedules.insert(
+        vesting_id,
+        VestingSchedule {
+            beneficiary,
+            total_amount: amount,
+            released_amount: 0,
+            start,
+            cliff,
+            duration,
+            revocable,
+            revoked: false,
+        },
+    );
+    new_state.next_vesting_id += 1;
+
+    let mut event_group = EventGroup::builder();
+    add_transfer_event(
+        &mut event_group,
+        context.contract_address,
+        context.sender,
+        context.contract_address,
+        context.sender,
+        amount,
+    );
+    (new_state, vec![event_group.build()])
+}
+
+/// Releases the tokens that have vested under a vesting schedule to its beneficiary.
+/// Once all tokens of the schedule have been released, the schedule is removed from state.
+/// The function throws if the schedule does not exist, if the caller is not the beneficiary,
+/// if the contract is paused, if the beneficiary is frozen, or if the beneficiary is not allowed
+/// to hold tokens in allowlist mode.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `vesting_id`: [`u64`], the id of the vesting schedule.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger,
+/// and an event group containing the [`TransferEvent`] to the beneficiary.
+#[action(shortname = 0x1C)]
+pub fn release(
+    context: ContractContext,
+    state: TokenState,
+    vesting_id: u64,
+) -> (TokenState, Vec<EventGroup>) {
+    let schedule = match state.vesting_schedules.get(&vesting_id) {
+        Some(schedule) => schedule,
+        None => TokenError::UnknownVestingSchedule.raise(),
+    };
+    if schedule.beneficiary != context.sender {
+        TokenError::Unauthorized.raise();
+    }
+    state.assert_not_paused();
+    state.assert_not_frozen(context.sender);
+    state.assert_can_receive(context.sender);
+    let amount = state.releasable_amount(vesting_id, context.block_time);
+
+    let mut new_state = state;
+    let schedule = new_state.vesting_schedules.get_mut(&vesting_id).unwrap();
+    schedule.released_amount += amount;
+    if schedule.released_amount == schedule.total_amount {
+        new_state.vesting_schedules.remove(&vesting_id);
+    }
+    new_state.credit(context.sender, amount, context.block_time);
+
+    let mut event_group = EventGroup::builder();
+    add_transfer_event(
+        &mut event_group,
+        context.contract_address,
+        context.contract_address,
+        context.sender,
+        context.sender,
+        amount,
+    );
+    (new_state, vec![event_group.build()])
+}
+
+/// Revokes a vesting schedule, returning the tokens that have not yet vested to the owner.
+/// The tokens that have vested remain releasable by the beneficiary.
+/// The function throws if the schedule does not exist, if the caller is not the owner,
+/// if the schedule is not revocable or has already been revoked, if the contract is paused,
+/// or if the owner is frozen.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `vesting_id`: [`u64`], the id of the vesting schedule.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the schedule revoked,
+/// and an event group containing the [`TransferEvent`] to the owner.
+#[action(shortname = 0x1D)]
+pub fn revoke(
+    context: ContractContext,
+    state: TokenState,
+    vesting_id: u64,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_owner(context.sender);
+    let schedule = match state.vesting_schedules.get(&vesting_id) {
+        Some(schedule) => schedule,
+        None => TokenError::UnknownVestingSchedule.raise(),
+    };
+    if !schedule.revocable || schedule.revoked {
+        TokenError::Unauthorized.raise();
+    }
+    state.assert_not_paused();
+    state.assert_not_frozen(context.sender);
+    let vested = schedule.vested_amount(context.block_time);
+    let unvested = schedule.total_amount - vested;
+
+    let mut new_state = state;
+    let schedule = new_state.vesting_schedules.get_mut(&vesting_id).unwrap();
+    schedule.total_amount = vested;
+    schedule.revoked = true;
+    if schedule.released_amount == schedule.total_amount {
+        new_state.vesting_schedules.remove(&vesting_id);
+    }
+    new_state.credit(context.sender, unvested, context.block_time);
+
+    let mut event_group = EventGroup::builder();
+    add_transfer_event(
+        &mut event_group,
+        context.contract_address,
+        context.contract_address,
+        context.sender,
+        context.sender,
+        unvested,
+    );
+    (new_state, vec![event_group.build()])
+}
+
+/// Locks `amount` of the caller's tokens, such that they cannot be transferred or burned
+/// before `unlock_at`. Locks add up, and each lock expires on its own.
+/// A lock whose unlock time has already passed has no effect.
+/// The function throws if the caller does not have enough unlocked tokens.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `amount`: [`u128`], amount of tokens to lock.
+///
+/// * `unlock_at`: [`i64`], the block time from which the tokens can be moved again.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the tokens locked.
+#[action(shortname = 0x1E)]
+pub fn lock(
+    context: ContractContext,
+    state: TokenState,
+    amount: u128,
+    unlock_at: i64,
+) -> (TokenState, Vec<EventGroup>) {
+    if amount > state.balance_of(context.sender) {
+        TokenError::InsufficientBalance.raise();
+    }
+    state.assert_unlocked(context.sender, amount, context.block_time);
+    let mut new_state = state;
+    new_state.remove_expired_locks(context.sender, context.block_time);
+    if unlock_at > context.block_time {
+        new_state.add_lock(context.sender, amount, unlock_at);
+    }
+    (new_state, vec![])
+}
+
+/// Takes a snapshot of all balances and the total supply, which can later be queried with
+/// `balance_of_at` and `total_supply_at`. The new snapshot gets the id `current_snapshot_id`,
+/// counting from 1.
+/// The function throws if the caller does not have the [`ROLE_SNAPSHOT`] role.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with a new snapshot.
+#[action(shortname = 0x1F)]
+pub fn snapshot(context: ContractContext, state: TokenState) -> (TokenState, Vec<EventGroup>) {
+    state.assert_role(ROLE_SNAPSHOT, context.sender);
+    let mut new_state = state;
+    new_state.current_snapshot_id += 1;
+    (new_state, vec![])
+}
+
+/// Sends the balance of `account` at a snapshot to the calling contract, by calling its action
+/// with the given `shortname`.
+/// The receiving action is called with the arguments
+/// `(account: Address, snapshot_id: u64, balance: u128, total_supply: u128)`.
+/// The function throws if the caller is not a contract, or if the snapshot does not exist.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `account`: [`Address`], the address to query the balance of.
+///
+/// * `snapshot_id`: [`u64`], the id of the snapshot.
+///
+/// * `shortname`: [`u32`], the shortname of the action to call on the caller.
+///
+/// ### Returns
+///
+/// The unchanged state object of type [`TokenContractState`],
+/// and an event group calling the caller with the balance.
+#[action(shortname = 0x22)]
+pub fn request_balance_at(
+    context: ContractContext,
+    state: TokenState,
+    account: Address,
+    snapshot_id: u64,
+    shortname: u32,
+) -> (TokenState, Vec<EventGroup>) {
+    if context.sender.address_type == AddressType::Account {
+        TokenError::InvalidReceiver.raise();
+    }
+    let balance = state.balance_of_at(account, snapshot_id);
+    let total_supply = state.total_supply_at(snapshot_id);
+    let mut event_group = EventGroup::builder();
+    event_group
+        .call(context.sender, Shortname::from_u32(shortname))
+        .argument(account)
+        .argument(snapshot_id)
+        .argument(balance)
+        .argument(total_supply)
+        .done();
+    (state, vec![event_group.build()])
+}
+
+/// Delegates the voting power of the caller to `delegatee`, replacing any previous delegate.
+/// The voting power of the caller is equal to its balance, and follows the balance as tokens
+/// are moved. Accounts only have votes once they have been delegated to, so holders that want
+/// to vote themselves must delegate to their own address.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `delegatee`: [`Address`], the address to delegate the voting power to.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with updated voting power.
+#[action(shortname = 0x23)]
+pub fn delegate(
+    context: ContractContext,
+    state: TokenState,
+    delegatee: Address,
+) -> (TokenState, Vec<EventGroup>) {
+    let mut new_state = state;
+    let balance = new_state.balance_of(context.sender);
+    if let Some(old_delegatee) = new_state.delegates(context.sender) {
+        let votes = new_state.get_votes(old_delegatee) - balance;
+        new_state.write_votes(old_delegatee, votes, context.block_time);
+    }
+    new_state.delegates.insert(context.sender, delegatee);
+    let votes = new_state.get_votes(delegatee) + balance;
+    new_state.write_votes(delegatee, votes, context.block_time);
+    (new_state, vec![])
+}
+
+/// Sends the voting power of `delegatee` at a block time to the calling contract, by calling its
+/// action with the given `shortname`. This allows e.g. a governor contract to read the voting
+/// power at the start time of a proposal.
+/// The receiving action is called with the arguments
+/// `(delegatee: Address, block_time: i64, votes: u128)`.
+/// The function throws if the caller is not a contract, or if `block_time` is not strictly before
+/// the current block time.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `delegatee`: [`Address`], the address to query the votes of.
+///
+/// * `block_time`: [`i64`], the block time to query the votes at.
+///
+/// * `shortname`: [`u32`], the shortname of the action to call on the caller.
+///
+/// ### Returns
+///
+/// The unchanged state object of type [`TokenContractState`],
+/// and an event group calling the caller with the votes.
+#[action(shortname = 0x24)]
+pub fn request_past_votes(
+    context: ContractContext,
+    state: TokenState,
+    delegatee: Address,
+    block_time: i64,
+    shortname: u32,
+) -> (TokenState, Vec<EventGroup>) {
+    if context.sender.address_type == AddressType::Account {
+        TokenError::InvalidReceiver.raise();
+    }
+    let votes = state.get_past_votes(delegatee, block_time, context.block_time);
+    let mut event_group = EventGroup::builder();
+    event_group
+        .call(context.sender, Shortname::from_u32(shortname))
+        .argument(delegatee)
+        .argument(block_time)
+        .argument(votes)
+        .done();
+    (state, vec![event_group.build()])
+}
+
+/// Creates a governance proposal that runs `action` if it passes.
+/// A snapshot is taken, and votes are weighted by the balances at this snapshot. Votes can be
+/// cast until `voting_period` has passed, after which the proposal can be finalized.
+/// The new proposal is given the id `next_proposal_id`.
+/// The function throws if the caller holds no tokens or less than `proposal_threshold_bps` of the
+/// circulating supply, if the caller
+/// already has a proposal that is open for voting, or if the description is longer than
+/// [`MAX_DESCRIPTION_LENGTH`].
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `description`: [`String`], human readable description of the proposal.
+///
+/// * `action`: [`ProposalAction`], the owner action to run if the proposal passes.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the new proposal.
+#[action(shortname = 0x25)]
+pub fn create_proposal(
+    context: ContractContext,
+    state: TokenState,
+    description: String,
+    action: ProposalAction,
+) -> (TokenState, Vec<EventGroup>) {
+    let balance = state.balance_of(context.sender);
+    let threshold = basis_points_of(state.circulating_supply(), state.proposal_threshold_bps);
+    if balance == 0 || balance < threshold {
+        TokenError::Unauthorized.raise();
+    }
+    if description.len() > MAX_DESCRIPTION_LENGTH {
+        TokenError::DescriptionTooLong.raise();
+    }
+    if state.proposals.iter().any(|(_, proposal)| {
+        proposal.proposer == context.sender
+            && proposal.status == (ProposalStatus::Active {})
+            && context.block_time <= proposal.end
+    }) {
+        TokenError::ProposalAlreadyActive.raise();
+    }
+    let end = match context.block_time.checked_add(state.voting_period) {
+        Some(end) => end,
+        None => TokenError::Overflow.raise(),
+    };
+    let mut new_state = state;
+    new_state.current_snapshot_id += 1;
+    // Only tokens held in balances at the snapshot can vote.
+    let quorum = basis_points_of(new_state.circulating_supply(), new_state.quorum_bps);
+    let proposal_id = new_state.next_proposal_id;
+    new_state.proposals.insert(
+        proposal_id,
+        Proposal {
+            proposer: context.sender,
+            description,
+            action,
+            snapshot_id: new_state.current_snapshot_id,
+            end,
+            quorum,
+            for_votes: 0,
+            against_votes: 0,
+            abstain_votes: 0,
+            voters: vec![],
+            status: ProposalStatus::Active {},
+        },
+    );
+    new_state.next_proposal_id += 1;
+    (new_state, vec![])
+}
+
+/// Casts a vote on a proposal, weighted by the balance of the caller when the proposal was
+/// created.
+/// The function throws if the proposal does not exist, if its voting window has ended, if the
+/// caller has already voted, or if the caller did not hold any tokens when it was created.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `proposal_id`: [`u64`], the id of the proposal.
+///
+/// * `vote`: [`VoteType`], whether to vote for, against or abstain.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the vote counted.
+#[action(shortname = 0x26)]
+pub fn cast_vote(
+    context: ContractContext,
+    state: TokenState,
+    proposal_id: u64,
+    vote: VoteType,
+) -> (TokenState, Vec<EventGroup>) {
+    let proposal = match state.proposals.get(&proposal_id) {
+        Some(proposal) => proposal,
+        None => TokenError::UnknownProposal.raise(),
+    };
+    if context.block_time > proposal.end {
+        TokenError::VotingClosed.raise();
+    }
+    if proposal.voters.contains(&context.sender) {
+        TokenError::AlreadyVoted.raise();
+    }
+    let weight = state.balance_of_at(context.sender, proposal.snapshot_id);
+    if weight == 0 {
+        TokenError::Unauthorized.raise();
+    }
+
+    let mut new_state = state;
+    let proposal = new_state.proposals.get_mut(&proposal_id).unwrap();
+    match vote {
+        VoteType::Against {} => proposal.against_votes += weight,
+        VoteType::For {} => proposal.for_votes += weight,
+        VoteType::Abstain {} => proposal.abstain_votes += weight,
+    }
+    proposal.voters.push(context.sender);
+    (new_state, vec![])
+}
+
+/// Finalizes a proposal after its voting window has ended. The proposal has passed if the votes
+/// for, against and abstaining together reach its quorum, and it received more votes for than
+/// against.
+/// The function throws if the proposal does not exist, if its voting window has not ended,
+/// or if it has already been finalized.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `proposal_id`: [`u64`], the id of the proposal.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the proposal succeeded or defeated.
+#[action(shortname = 0x27)]
+pub fn finalize_proposal(
+    context: ContractContext,
+    state: TokenState,
+    proposal_id: u64,
+) -> (TokenState, Vec<EventGroup>) {
+    let mut new_state = state;
+    let proposal = match new_state.proposals.get_mut(&proposal_id) {
+        Some(proposal) => proposal,
+        None => TokenError::UnknownProposal.raise(),
+    };
+    if proposal.status != (ProposalStatus::Active {}) {
+        TokenError::ProposalAlreadyFinalized.raise();
+    }
+    if context.block_time <= proposal.end {
+        TokenError::VotingNotEnded.raise();
+    }
+    let turnout = proposal.for_votes + proposal.against_votes + proposal.abstain_votes;
+    if turnout >= proposal.quorum && proposal.for_votes > proposal.against_votes {
+        proposal.status = ProposalStatus::Succeeded {};
+    } else {
+        proposal.status = ProposalStatus::Defeated {};
+    }
+    (new_state, vec![])
+}
+
+/// Executes a proposal that has passed, running its action with the authority of the owner.
+/// An `Unpause` action can only end a pause that was started by a `Pause` proposal, not one
+/// started by a pauser.
+/// The function throws if ownership has been renounced, if the proposal does not exist, if it
+/// has not passed, if it has already been executed, or if its action fails or is not allowed.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `proposal_id`: [`u64`], the id of the proposal.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] after running the action, and for a
+/// `Mint` action an event group containing the [`TransferEvent`] from the contract.
+#[action(shortname = 0x28)]
+pub fn execute_proposal(
+    context: ContractContext,
+    state: TokenState,
+    proposal_id: u64,
+) -> (TokenState, Vec<EventGroup>) {
+    if state.owner.is_none() {
+        TokenError::Unauthorized.raise();
+    }
+    let proposal = match state.proposals.get(&proposal_id) {
+        Some(proposal) => proposal,
+        None => TokenError::UnknownProposal.raise(),
+    };
+    if proposal.status != (ProposalStatus::Succeeded {}) {
+        TokenError::ProposalNotPassed.raise();
+    }
+    let action = proposal.action.clone();
+
+    let mut new_state = state;
+    new_state.proposals.get_mut(&proposal_id).unwrap().status = ProposalStatus::Executed {};
+    let mut events = vec![];
+    match action {
+        ProposalAction::Mint { to, amount } => {
+            new_state = core_mint(new_state, to, amount, context.block_time).0;
+            let mut event_group = EventGroup::builder();
+            add_transfer_event(
+                &mut event_group,
+                context.contract_address,
+                context.contract_address,
+                to,
+                context.sender,
+                amount,
+            );
+            events.push(event_group.build());
+        }
+        ProposalAction::Pause {} => {
+            if !new_state.paused {
+                new_state.paused = true;
+                new_state.paused_by_governance = true;
+            }
+        }
+        ProposalAction::Unpause {} => {
+            if new_state.paused && !new_state.paused_by_governance {
+                TokenError::Unauthorized.raise();
+            }
+            new_state.paused = false;
+            new_state.paused_by_governance = false;
+        }
+    }
+    (new_state, events)
+}
+
+/// Sets the parameters of governance proposals. The new parameters apply to proposals created
+/// from now on.
+/// The function throws if the caller is not the owner, if `voting_period` is not positive, or if
+/// `quorum_bps` or `proposal_threshold_bps` is more than [`BASIS_POINTS`].
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `voting_period`: [`i64`], the length of the voting window of proposals, in block time.
+///
+/// * `quorum_bps`: [`u16`], the share of the circulating supply, in basis points, that must vote on a
+///   proposal for it to pass.
+///
+/// * `proposal_threshold_bps`: [`u16`], the share of the circulating supply, in basis points,
+///   that a holder needs to create a proposal.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the new governance parameters.
+#[action(shortname = 0x32)]
+pub fn set_governance_parameters(
+    context: ContractContext,
+    state: TokenState,
+    voting_period: i64,
+    quorum_bps: u16,
+    proposal_threshold_bps: u16,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_owner(context.sender);
+    if voting_period <= 0
+        || quorum_bps as u128 > BASIS_POINTS
+        || proposal_threshold_bps as u128 > BASIS_POINTS
+    {
+        TokenError::InvalidGovernanceParameters.raise();
+    }
+    let mut new_state = state;
+    new_state.voting_period = voting_period;
+    new_state.quorum_bps = quorum_bps;
+    new_state.proposal_threshold_bps = proposal_threshold_bps;
+    (new_state, vec![])
+}
+
+/// Deposits `amount` of the caller's tokens as rewards, to be shared among all holders by their
+/// current balance. Each holder can claim their share with `claim_rewards`.
+/// Rewards that cannot be divided evenly are carried over to the next deposit, such that no more
+/// than the deposited rewards are ever paid out.
+/// The function throws if the caller is not the owner, if the owner does not have enough
+/// unlocked tokens, if the contract is paused, if the owner is frozen, or if `amount` scaled by
+/// [`REWARD_PRECISION`] or the new `reward_per_token` cannot be represented.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `amount`: [`u128`], amount of tokens to distribute.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the rewards distributed,
+/// and an event group containing the [`TransferEvent`] to the contract.
+#[action(shortname = 0x29)]
+pub fn deposit_rewards(
+    context: ContractContext,
+    state: TokenState,
+    amount: u128,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_owner(context.sender);
+    state.assert_not_paused();
+    state.assert_not_frozen(context.sender);
+    state.assert_unlocked(context.sender, amount, context.block_time);
+    let scaled_amount = match amount
+        .checked_mul(REWARD_PRECISION)
+        .and_then(|scaled| scaled.checked_add(state.reward_remainder))
+    {
+        Some(scaled_amount) => scaled_amount,
+        None => TokenError::Overflow.raise(),
+    };
+
+    let mut new_state = state;
+    new_state.debit(context.sender, amount, context.block_time);
+    new_state.reward_pool += amount;
+    let eligible_supply = new_state.circulating_supply();
+    match scaled_amount.checked_div(eligible_supply) {
+        Some(increase) => {
+            new_state.reward_per_token = match new_state.reward_per_token.checked_add(increase) {
+                Some(reward_per_token) => reward_per_token,
+                None => TokenError::Overflow.raise(),
+            };
+            new_state.reward_remainder = scaled_amount % eligible_supply;
+        }
+        None => new_state.reward_remainder = scaled_amount,
+    }
+
+    let mut event_group = EventGroup::builder();
+    add_transfer_event(
+        &mut event_group,
+        context.contract_address,
+        context.sender,
+        context.contract_address,
+        context.sender,
+        amount,
+    );
+    (new_state, vec![event_group.build()])
+}
+
+/// Pays out the rewards of the caller.
+/// The function throws if the contract is paused, if the caller is frozen, or if the caller is
+/// not allowed to hold tokens in allowlist mode.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger,
+/// and an event group containing the [`TransferEvent`] to the caller.
+#[action(shortname = 0x2A)]
+pub fn claim_rewards(context: ContractContext, state: TokenState) -> (TokenState, Vec<EventGroup>) {
+    state.assert_not_paused();
+    state.assert_not_frozen(context.sender);
+    state.assert_can_receive(context.sender);
+    let mut new_state = state;
+    new_state.settle_rewards(context.sender);
+    let amount = new_state
+        .unclaimed_rewards
+        .remove(&context.sender)
+        .unwrap_or(0);
+    new_state.reward_pool -= amount;
+    new_state.credit(context.sender, amount, context.block_time);
+
+    let mut event_group = EventGroup::builder();
+    add_transfer_event(
+        &mut event_group,
+        context.contract_address,
+        context.contract_address,
+        context.sender,
+        context.sender,
+        amount,
+    );
+    (new_state, vec![event_group.build()])
+}
+
+/// Stakes `amount` of the caller's tokens, moving them out of the balance of the caller into the
+/// staking ledger, where they earn staking rewards.
+/// The function throws if the caller does not have enough unlocked tokens, if the contract is
+/// paused, or if the caller is frozen.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `amount`: [`u128`], amount of tokens to stake.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with the tokens staked,
+/// and an event group containing the [`TransferEvent`] to the contract.
+#[action(shortname = 0x2B)]
+pub fn stake(
+    context: ContractContext,
+    state: TokenState,
+    amount: u128,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_not_paused();
+    state.assert_not_frozen(context.sender);
+    state.assert_unlocked(context.sender, amount, context.block_time);
+    let mut new_state = state;
+    new_state.debit(context.sender, amount, context.block_time);
+    new_state.staking.update(context.block_time);
+    new_state.staking.settle(context.sender);
+    let staked = new_state.staking.stake_of(context.sender);
+    new_state
+        .staking
+        .stakes
+        .insert(context.sender, staked + amount);
+    new_state.staking.total_staked += amount;
 
-        allowed_from_owner.insert(spender, amount);
-    }
+    let mut event_group = EventGroup::builder();
+    add_transfer_event(
+        &mut event_group,
+        context.contract_address,
+        context.sender,
+        context.contract_address,
+        context.sender,
+        amount,
+    );
+    (new_state, vec![event_group.build()])
 }
 
-/// Initial function to bootstrap the contracts state. Must return the state-struct.
+/// Unstakes `amount` of the caller's tokens, moving them back to the balance of the caller.
+/// If an unbonding period has been set, the tokens are locked until it has passed, or until the
+/// last representable block time if the period ends later than that.
+/// The function throws if the caller has not staked enough tokens, if the contract is paused,
+/// or if the caller is frozen.
 ///
 /// ### Parameters:
 ///
-/// * `ctx`: [`ContractContext`], initial context.
-///
-/// * `name`: [`String`], the name of the token - e.g. "MyToken".\
-///
-/// * `symbol`: [`String`], the symbol of the token. E.g. "HIX".\
+/// * `context`: [`ContractContext`], the context for the action call.
 ///
-/// * `decimals`: [`u8`], the number of decimals the token uses - e.g. 8,
-/// means to divide the token amount by `100000000` to get its user representation.\
+/// * `state`: [`TokenContractState`], the current state of the contract.
 ///
-/// * `total_supply`: [`u128`], current amount of tokens for the TokenContract.
+/// * `amount`: [`u128`], amount of tokens to unstake.
 ///
-/// ### Returns:
+/// ### Returns
 ///
-/// The new state object of type [`TokenContractState`] with an initialized ledger.
-#[init]
-pub fn initialize(
-    ctx: ContractContext,
-    name: String,
-    symbol: String,
-    decimals: u8,
-    total_supply: u128,
+/// The new state object of type [`TokenContractState`] with the tokens unstaked,
+/// and an event group containing the [`TransferEvent`] to the caller.
+#[action(shortname = 0x2C)]
+pub fn unstake(
+    context: ContractContext,
+    state: TokenState,
+    amount: u128,
 ) -> (TokenState, Vec<EventGroup>) {
-    let mut balances = SortedVecMap::new();
-    balances.insert(ctx.sender, total_supply);
-
-    let state = TokenState {
-        name,
-        symbol,
-        decimals,
-        owner: ctx.sender,
-        total_supply,
-        balances,
-        allowed: SortedVecMap::new(),
+    state.assert_not_paused();
+    state.assert_not_frozen(context.sender);
+    let new_staked = match state.staking.stake_of(context.sender).checked_sub(amount) {
+        Some(new_staked) => new_staked,
+        None => TokenError::InsufficientBalance.raise(),
     };
+    let mut new_state = state;
+    new_state.staking.update(context.block_time);
+    new_state.staking.settle(context.sender);
+    if new_staked == 0 {
+        new_state.staking.stakes.remove(&context.sender);
+        new_state
+            .staking
+            .reward_paid_per_token
+            .remove(&context.sender);
+    } else {
+        new_state.staking.stakes.insert(context.sender, new_staked);
+    }
+    new_state.staking.total_staked -= amount;
+    new_state.credit(context.sender, amount, context.block_time);
+    let unlock_at = context
+        .block_time
+        .saturating_add(new_state.staking.unbonding_period);
+    if unlock_at > context.block_time {
+        new_state.add_lock(context.sender, amount, unlock_at);
+    }
 
-    (state, vec![])
+    let mut event_group = EventGroup::builder();
+    add_transfer_event(
+        &mut event_group,
+        context.contract_address,
+        context.contract_address,
+        context.sender,
+        context.sender,
+        amount,
+    );
+    (new_state, vec![event_group.build()])
 }
 
-/// Represents the type of a transfer.
-#[derive(ReadWriteRPC, CreateTypeSpec)]
-pub struct Transfer {
-    /// The address to transfer to.
-    pub to: Address,
-    /// The amount to transfer.
-    pub amount: u128,
+/// Pays out the staking rewards of the caller.
+/// The function throws if the contract is paused, if the caller is frozen, or if the caller is
+/// not allowed to hold tokens in allowlist mode.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger,
+/// and an event group containing the [`TransferEvent`] to the caller.
+#[action(shortname = 0x2D)]
+pub fn claim(context: ContractContext, state: TokenState) -> (TokenState, Vec<EventGroup>) {
+    state.assert_not_paused();
+    state.assert_not_frozen(context.sender);
+    state.assert_can_receive(context.sender);
+    let mut new_state = state;
+    new_state.staking.update(context.block_time);
+    new_state.staking.settle(context.sender);
+    let amount = new_state
+        .staking
+        .unclaimed_rewards
+        .remove(&context.sender)
+        .unwrap_or(0);
+    new_state.staking.accrued_rewards -= amount;
+    new_state.credit(context.sender, amount, context.block_time);
+
+    let mut event_group = EventGroup::builder();
+    add_transfer_event(
+        &mut event_group,
+        context.contract_address,
+        context.contract_address,
+        context.sender,
+        context.sender,
+        amount,
+    );
+    (new_state, vec![event_group.build()])
 }
 
-/// Transfers `amount` of tokens to address `to` from the caller.
-/// The function throws if the message caller's account
-/// balance does not have enough tokens to spend.
-/// If the sender's account goes to 0, the sender's address is removed from state.
+/// Moves `amount` of the caller's tokens into the staking reserve, from which staking rewards
+/// are paid.
+/// The function throws if the caller is not the owner, if the owner does not have enough
+/// unlocked tokens, if the contract is paused, if the owner is frozen, or if the reserve scaled
+/// by [`REWARD_PRECISION`] cannot be represented.
 ///
 /// ### Parameters:
 ///
@@ -168,27 +3579,46 @@ pub struct Transfer {
 ///
 /// * `state`: [`TokenContractState`], the current state of the contract.
 ///
-/// * `to`: [`Address`], the address to transfer to.
-///
-/// * `amount`: [`u128`], amount to transfer.
+/// * `amount`: [`u128`], amount of tokens to add to the reserve.
 ///
 /// ### Returns
 ///
-/// The new state object of type [`TokenContractState`] with an updated ledger.
-#[action(shortname = 0x01)]
-pub fn transfer(
+/// The new state object of type [`TokenContractState`] with the reserve funded,
+/// and an event group containing the [`TransferEvent`] to the contract.
+#[action(shortname = 0x2E)]
+pub fn fund_staking_reserve(
     context: ContractContext,
     state: TokenState,
-    to: Address,
     amount: u128,
 ) -> (TokenState, Vec<EventGroup>) {
-    core_transfer(context.sender, state, to, amount)
+    state.assert_owner(context.sender);
+    state.assert_not_paused();
+    state.assert_not_frozen(context.sender);
+    state.assert_unlocked(context.sender, amount, context.block_time);
+    let mut new_state = state;
+    new_state.staking.update(context.block_time);
+    new_state.debit(context.sender, amount, context.block_time);
+    let new_reserve = new_state.staking.reserve + amount;
+    if new_reserve.checked_mul(REWARD_PRECISION).is_none() {
+        TokenError::Overflow.raise();
+    }
+    new_state.staking.reserve = new_reserve;
+
+    let mut event_group = EventGroup::builder();
+    add_transfer_event(
+        &mut event_group,
+        context.contract_address,
+        context.sender,
+        context.contract_address,
+        context.sender,
+        amount,
+    );
+    (new_state, vec![event_group.build()])
 }
 
-/// Transfers a bulk of `amount` of tokens to address `to` from the caller.
-/// The function throws if the message caller's account
-/// balance does not have enough tokens to spend.
-/// If the sender's account goes to 0, the sender's address is removed from state.
+/// Sets the staking reward rate and the unbonding period. Rewards accrued until now are paid
+/// at the previous rate, and the new unbonding period applies to tokens unstaked from now on.
+/// The function throws if the caller is not the owner, or if `unbonding_period` is negative.
 ///
 /// ### Parameters:
 ///
@@ -196,29 +3626,34 @@ pub fn transfer(
 ///
 /// * `state`: [`TokenContractState`], the current state of the contract.
 ///
-/// * `transfers`: [`Vec[Transfer]`], vector of [the address to transfer to, amount to transfer].
+/// * `reward_rate`: [`u128`], the rewards paid per unit of block time, shared among all stakers.
+///
+/// * `unbonding_period`: [`i64`], the time after unstaking before the tokens can be moved.
 ///
 /// ### Returns
 ///
-/// The new state object of type [`TokenContractState`] with an updated ledger.
-#[action(shortname = 0x02)]
-pub fn bulk_transfer(
+/// The new state object of type [`TokenContractState`] with the new staking parameters.
+#[action(shortname = 0x2F)]
+pub fn set_staking_parameters(
     context: ContractContext,
     state: TokenState,
-    transfers: Vec<Transfer>,
+    reward_rate: u128,
+    unbonding_period: i64,
 ) -> (TokenState, Vec<EventGroup>) {
-    let mut new_state = state;
-    for t in transfers {
-        new_state = core_transfer(context.sender, new_state, t.to, t.amount).0;
+    state.assert_owner(context.sender);
+    if unbonding_period < 0 {
+        TokenError::InvalidStakingParameters.raise();
     }
+    let mut new_state = state;
+    new_state.staking.update(context.block_time);
+    new_state.staking.reward_rate = reward_rate;
+    new_state.staking.unbonding_period = unbonding_period;
     (new_state, vec![])
 }
 
-/// Transfers `amount` of tokens from address `from` to address `to`.\
-/// This requires that the sender is allowed to do the transfer by the `from`
-/// account through the `approve` action.
-/// The function throws if the message caller's account
-/// balance does not have enough tokens to spend, or if the tokens were not approved.
+/// Sets the fee taken on transfers, or removes it if `fee_config` is `None`.
+/// The function throws if the caller is not the owner, if the rate exceeds [`BASIS_POINTS`], or
+/// if the recipient is frozen or not allowed to hold tokens in allowlist mode.
 ///
 /// ### Parameters:
 ///
@@ -226,31 +3661,33 @@ pub fn bulk_transfer(
 ///
 /// * `state`: [`TokenContractState`], the current state of the contract.
 ///
-/// * `from`: [`Address`], the address to transfer from.
-///
-/// * `to`: [`Address`], the address to transfer to.
-///
-/// * `amount`: [`u128`], amount to transfer.
+/// * `fee_config`: [`Option<FeeConfig>`], the new fee configuration.
 ///
 /// ### Returns
 ///
-/// The new state object of type [`TokenContractState`] with an updated ledger.
-#[action(shortname = 0x03)]
-pub fn transfer_from(
+/// The new state object of type [`TokenContractState`] with the new fee configuration.
+#[action(shortname = 0x30)]
+pub fn set_fee_config(
     context: ContractContext,
     state: TokenState,
-    from: Address,
-    to: Address,
-    amount: u128,
+    fee_config: Option<FeeConfig>,
 ) -> (TokenState, Vec<EventGroup>) {
-    core_transfer_from(context.sender, state, from, to, amount)
+    state.assert_owner(context.sender);
+    if let Some(config) = &fee_config {
+        if config.rate_bps as u128 > BASIS_POINTS {
+            TokenError::InvalidFeeConfig.raise();
+        }
+        state.assert_not_frozen(config.recipient);
+        state.assert_can_receive(config.recipient);
+    }
+    let mut new_state = state;
+    new_state.fee_config = fee_config;
+    (new_state, vec![])
 }
 
-/// Transfers a bulk of `amount` of tokens to address `to` from address `from` .\
-/// This requires that the sender is allowed to do the transfer by the `from`
-/// account through the `approve` action.
-/// The function throws if the message caller's account
-/// balance does not have enough tokens to spend, or if the tokens were not approved.
+/// Adds `account` to or removes it from the fee exemptions. Transfers from or to an exempt
+/// account are not charged a fee. The owner is always exempt.
+/// The function throws if the caller is not the owner.
 ///
 /// ### Parameters:
 ///
@@ -258,29 +3695,36 @@ pub fn transfer_from(
 ///
 /// * `state`: [`TokenContractState`], the current state of the contract.
 ///
-/// * `from`: [`Address`], the address to transfer from.
+/// * `account`: [`Address`], the address to exempt or charge.
 ///
-/// * `transfers`: [`Vec[Transfer]`], vector of [the address to transfer to, amount to transfer].
+/// * `exempt`: [`bool`], whether `account` should be exempt from transfer fees.
 ///
 /// ### Returns
 ///
-/// The new state object of type [`TokenContractState`] with an updated ledger.
-#[action(shortname = 0x04)]
-pub fn bulk_transfer_from(
+/// The new state object of type [`TokenContractState`] with updated fee exemptions.
+#[action(shortname = 0x31)]
+pub fn set_fee_exempt(
     context: ContractContext,
     state: TokenState,
-    from: Address,
-    transfers: Vec<Transfer>,
+    account: Address,
+    exempt: bool,
 ) -> (TokenState, Vec<EventGroup>) {
+    state.assert_owner(context.sender);
     let mut new_state = state;
-    for t in transfers {
-        new_state = core_transfer_from(context.sender, new_state, from, t.to, t.amount).0;
+    new_state
+        .fee_exempt
+        .retain(|exempt_account| *exempt_account != account);
+    if exempt {
+        new_state.fee_exempt.push(account);
     }
     (new_state, vec![])
 }
 
-/// Allows `spender` to withdraw from the owners account multiple times, up to the `amount`.
-/// If this function is called again it overwrites the current allowance with `amount`.
+/// Receives a [`TransferEvent`] emitted by this contract.
+/// The notification is a call from the contract to itself, making the transfer visible to
+/// indexers and other observers of the chain. The state is left unchanged.
+/// The function throws if it is not called by the contract itself.
+/// The shortname must equal [`TRANSFER_EVENT_SHORTNAME`].
 ///
 /// ### Parameters:
 ///
@@ -288,28 +3732,59 @@ pub fn bulk_transfer_from(
 ///
 /// * `state`: [`TokenContractState`], the current state of the contract.
 ///
-/// * `spender`: [`Address`], the address of the spender.
+/// * `event`: [`TransferEvent`], the emitted notification.
 ///
-/// * `amount`: [`u128`], approved amount.
+/// ### Returns
+///
+/// The unchanged state object of type [`TokenContractState`].
+#[action(shortname = 0x20)]
+pub fn transfer_event(
+    context: ContractContext,
+    state: TokenState,
+    event: TransferEvent,
+) -> (TokenState, Vec<EventGroup>) {
+    if context.sender != context.contract_address {
+        TokenError::Unauthorized.raise();
+    }
+    (state, vec![])
+}
+
+/// Receives an [`ApprovalEvent`] emitted by this contract.
+/// The notification is a call from the contract to itself, making the approval visible to
+/// indexers and other observers of the chain. The state is left unchanged.
+/// The function throws if it is not called by the contract itself.
+/// The shortname must equal [`APPROVAL_EVENT_SHORTNAME`].
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `event`: [`ApprovalEvent`], the emitted notification.
 ///
 /// ### Returns
 ///
-/// The new state object of type [`TokenContractState`] with an updated ledger.
-#[action(shortname = 0x05)]
-pub fn approve(
+/// The unchanged state object of type [`TokenContractState`].
+#[action(shortname = 0x21)]
+pub fn approval_event(
     context: ContractContext,
     state: TokenState,
-    spender: Address,
-    amount: u128,
+    event: ApprovalEvent,
 ) -> (TokenState, Vec<EventGroup>) {
-    let mut new_state = state;
-    new_state.update_allowance(context.sender, spender, amount);
-    (new_state, vec![])
+    if context.sender != context.contract_address {
+        TokenError::Unauthorized.raise();
+    }
+    (state, vec![])
 }
 
 /// Transfers `amount` of tokens to address `to` from the caller.
+/// If a transfer fee is configured and neither account is exempt, the fee is deducted from the
+/// amount received by `to` and paid to the fee recipient.
 /// The function throws if the message caller's account
-/// balance does not have enough tokens to spend.
+/// balance does not have enough tokens to spend, if some of the tokens are locked,
+/// if the contract is paused, if either account is frozen, or if `to` is not allowed to hold
+/// tokens in allowlist mode.
 /// If the sender's account goes to 0, the sender's address is removed from state.
 ///
 /// ### Parameters:
@@ -322,6 +3797,8 @@ pub fn approve(
 ///
 /// * `amount`: [`u128`], amount to transfer.
 ///
+/// * `block_time`: [`i64`], the block time of the transaction, used to check locked tokens.
+///
 /// ### Returns
 ///
 /// The new state object of type [`TokenContractState`] with an updated ledger.
@@ -330,31 +3807,52 @@ pub fn core_transfer(
     state: TokenState,
     to: Address,
     amount: u128,
+    block_time: i64,
 ) -> (TokenState, Vec<EventGroup>) {
+    let (mut new_state, received) = withdraw_for_transfer(sender, state, to, amount, block_time);
+    new_state.credit(to, received, block_time);
+    (new_state, vec![])
+}
+
+/// Takes `amount` of tokens from `sender` for a transfer to `to`, and pays any transfer fee to
+/// the fee recipient, performing the same checks as `core_transfer`. The received amount is not
+/// yet credited to `to`.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger, and the amount
+/// to be received by `to` after the fee.
+fn withdraw_for_transfer(
+    sender: Address,
+    state: TokenState,
+    to: Address,
+    amount: u128,
+    block_time: i64,
+) -> (TokenState, u128) {
+    state.assert_not_paused();
+    state.assert_not_frozen(sender);
+    state.assert_not_frozen(to);
+    state.assert_can_receive(to);
+    state.assert_unlocked(sender, amount, block_time);
+    let fee = state.transfer_fee(sender, to, amount);
     let mut new_state = state;
-    let from_amount = new_state.balance_of(sender);
-    let o_new_from_amount = from_amount.checked_sub(amount);
-    match o_new_from_amount {
-        Some(new_from_amount) => {
-            new_state.balances.insert(sender, new_from_amount);
-        }
-        None => {
-            panic!("Underflow in transfer - owner did not have enough tokens");
+    new_state.remove_expired_locks(sender, block_time);
+    new_state.debit(sender, amount, block_time);
+    if let Some(fee_config) = &new_state.fee_config {
+        if fee > 0 {
+            let recipient = fee_config.recipient;
+            new_state.credit(recipient, fee, block_time);
         }
     }
-    let to_amount = new_state.balance_of(to);
-    new_state.balances.insert(to, to_amount.add(amount));
-    if new_state.balance_of(sender) == 0 {
-        new_state.balances.remove(&sender);
-    };
-    (new_state, vec![])
+    (new_state, amount - fee)
 }
 
 /// Transfers `amount` of tokens from address `from` to address `to`.\
 /// This requires that the sender is allowed to do the transfer by the `from`
 /// account through the `approve` action.
 /// The function throws if the message caller's account
-/// balance does not have enough tokens to spend, or if the tokens were not approved.
+/// balance does not have enough tokens to spend, if the tokens were not approved,
+/// if the contract is paused, or if any of the involved accounts is frozen.
 ///
 /// ### Parameters:
 ///
@@ -368,6 +3866,8 @@ pub fn core_transfer(
 ///
 /// * `amount`: [`u128`], amount to transfer.
 ///
+/// * `block_time`: [`i64`], the block time of the transaction, used to check allowance expiry.
+///
 /// ### Returns
 ///
 /// The new state object of type [`TokenContractState`] with an updated ledger.
@@ -377,17 +3877,213 @@ pub fn core_transfer_from(
     from: Address,
     to: Address,
     amount: u128,
+    block_time: i64,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_not_paused();
+    state.assert_not_frozen(sender);
+    state.assert_not_frozen(from);
+    let mut new_state = state;
+    new_state.spend_allowance(from, sender, amount, block_time);
+    core_transfer(from, new_state, to, amount, block_time)
+}
+
+/// Destroys `amount` of tokens from the account of `owner`, decreasing the total supply.
+/// The function throws if the account does not have enough tokens to burn,
+/// if some of the tokens are locked, if the contract is paused, or if the account is frozen.
+/// If the account goes to 0, the address is removed from state.
+///
+/// ### Parameters:
+///
+/// * `owner`: [`Address`], the address to burn tokens from.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `amount`: [`u128`], amount to burn.
+///
+/// * `block_time`: [`i64`], the block time of the transaction, used to check locked tokens.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger.
+pub fn core_burn(
+    owner: Address,
+    state: TokenState,
+    amount: u128,
+    block_time: i64,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_not_paused();
+    state.assert_not_frozen(owner);
+    state.assert_unlocked(owner, amount, block_time);
+    let mut new_state = state;
+    new_state.remove_expired_locks(owner, block_time);
+    new_state.debit(owner, amount, block_time);
+    let new_total_supply = new_state.total_supply - amount;
+    new_state.set_total_supply(new_total_supply);
+    (new_state, vec![])
+}
+
+/// Destroys `amount` of tokens from address `from`, spending the allowance of `sender`.\
+/// This requires that the sender is allowed to use the tokens of the `from`
+/// account through the `approve` action.
+/// The function throws if the `from` account does not have enough tokens to burn,
+/// if the tokens were not approved, or if any of the involved accounts is frozen.
+///
+/// ### Parameters:
+///
+/// * `sender`: [`Address`], the sender of the transaction.
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `from`: [`Address`], the address to burn tokens from.
+///
+/// * `amount`: [`u128`], amount to burn.
+///
+/// * `block_time`: [`i64`], the block time of the transaction, used to check allowance expiry.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger.
+pub fn core_burn_from(
+    sender: Address,
+    state: TokenState,
+    from: Address,
+    amount: u128,
+    block_time: i64,
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_not_frozen(sender);
+    state.assert_not_frozen(from);
+    let mut new_state = state;
+    new_state.spend_allowance(from, sender, amount, block_time);
+    core_burn(from, new_state, amount, block_time)
+}
+
+/// Creates `amount` new tokens and assigns them to address `to`, increasing the total supply.
+/// The function throws if the contract is paused, if `to` is frozen or not allowlisted, or if the
+/// new total supply would exceed the maximum supply.
+///
+/// ### Parameters:
+///
+/// * `state`: [`TokenContractState`], the current state of the contract.
+///
+/// * `to`: [`Address`], the address to mint tokens to.
+///
+/// * `amount`: [`u128`], amount to mint.
+///
+/// * `block_time`: [`i64`], the block time of the transaction, used to checkpoint voting power.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenContractState`] with an updated ledger.
+pub fn core_mint(
+    state: TokenState,
+    to: Address,
+    amount: u128,
+    block_time: i64,
 ) -> (TokenState, Vec<EventGroup>) {
+    state.assert_not_paused();
     let mut new_state = state;
-    let from_allowed = new_state.allowance(from, sender);
-    let o_new_allowed_amount = from_allowed.checked_sub(amount);
-    match o_new_allowed_amount {
-        Some(new_allowed_amount) => {
-            new_state.update_allowance(from, sender, new_allowed_amount);
+    let new_total_supply = match new_state.total_supply.checked_add(amount) {
+        Some(new_total_supply) => new_total_supply,
+        None => TokenError::Overflow.raise(),
+    };
+    if let Some(cap) = new_state.max_supply {
+        if new_total_supply > cap {
+            TokenError::MaxSupplyExceeded.raise();
         }
-        None => {
-            panic!("Underflow in transfer_from - tokens has not been approved for transfer");
+    }
+    new_state.assert_not_frozen(to);
+    new_state.assert_can_receive(to);
+    new_state.set_total_supply(new_total_supply);
+    new_state.credit(to, amount, block_time);
+    (new_state, vec![])
+}
+
+/// Adds the [`TransferEvent`]s of a transfer made with `core_transfer` to `event_group`: one for
+/// the amount received by `to`, and, if a fee was charged, one for the fee paid to the fee
+/// recipient.
+fn add_transfer_events(
+    event_group: &mut EventGroupBuilder,
+    contract: Address,
+    state: &TokenState,
+    from: Address,
+    to: Address,
+    spender: Address,
+    amount: u128,
+) {
+    let fee = state.transfer_fee(from, to, amount);
+    add_transfer_event(event_group, contract, from, to, spender, amount - fee);
+    if let Some(fee_config) = &state.fee_config {
+        if fee > 0 {
+            add_transfer_event(
+                event_group,
+                contract,
+                from,
+                fee_config.recipient,
+                spender,
+                fee,
+            );
         }
     }
-    core_transfer(from, new_state, to, amount)
+}
+
+/// Adds a [`TransferEvent`] to `event_group`, delivered to the `transfer_event` action of
+/// `contract`.
+fn add_transfer_event(
+    event_group: &mut EventGroupBuilder,
+    contract: Address,
+    from: Address,
+    to: Address,
+    spender: Address,
+    amount: u128,
+) {
+    event_group
+        .call(contract, Shortname::from_u32(TRANSFER_EVENT_SHORTNAME))
+        .argument(TransferEvent {
+            from,
+            to,
+            spender,
+            amount,
+        })
+        .done();
+}
+
+/// Adds an [`ApprovalEvent`] with the remaining allowance from `owner` to `spender` to
+/// `event_group`, after the allowance has been spent.
+fn add_spent_allowance_event(
+    event_group: &mut EventGroupBuilder,
+    contract: Address,
+    state: &TokenState,
+    owner: Address,
+    spender: Address,
+    block_time: i64,
+) {
+    add_approval_event(
+        event_group,
+        contract,
+        owner,
+        spender,
+        state.allowance(owner, spender, block_time),
+        state.allowance_expiry(owner, spender, block_time),
+    );
+}
+
+/// Adds an [`ApprovalEvent`] to `event_group`, delivered to the `approval_event` action of
+/// `contract`.
+fn add_approval_event(
+    event_group: &mut EventGroupBuilder,
+    contract: Address,
+    owner: Address,
+    spender: Address,
+    amount: u128,
+    expires_at: Option<i64>,
+) {
+    event_group
+        .call(contract, Shortname::from_u32(APPROVAL_EVENT_SHORTNAME))
+        .argument(ApprovalEvent {
+            owner,
+            spender,
+            amount,
+            expires_at,
+        })
+        .done();
 }
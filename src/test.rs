@@ -1,12 +1,25 @@
 #[cfg(test)]
 mod test_contract {
     use pbc_contract_common::address::{Address, AddressType};
-    use pbc_contract_common::context::ContractContext;
+    use pbc_contract_common::context::{CallbackContext, ContractContext, ExecutionResult};
     use pbc_contract_common::Hash;
+    use pbc_traits::ReadRPC;
     use std::ops::Sub;
 
     use crate::{
-        approve, bulk_transfer, bulk_transfer_from, initialize, transfer, transfer_from, Transfer,
+        accept_ownership, add_to_allowlist, approve, bulk_transfer, bulk_transfer_from, burn,
+        burn_from, cast_vote, claim, claim_rewards, create_proposal, create_vesting,
+        decrease_allowance, delegate, deposit_rewards, execute_proposal, finalize_proposal, freeze,
+        fund_staking_reserve, grant_role, increase_allowance, initialize, lock, mint, pause,
+        propose_owner, release, remove_from_allowlist, renounce_ownership, renounce_role,
+        request_balance_at, request_past_votes, revoke, revoke_role, reward_for, safe_approve,
+        set_fee_config, set_fee_exempt, set_governance_parameters, set_role_admin,
+        set_staking_parameters, snapshot, stake, transfer, transfer_and_call,
+        transfer_and_call_callback, transfer_event, transfer_from, unfreeze, unpause, unstake,
+        ApprovalEvent, FeeConfig, ProposalAction, ProposalStatus, TokenError, Transfer,
+        TransferEvent, VoteType, APPROVAL_EVENT_SHORTNAME, MAX_DESCRIPTION_LENGTH,
+        REWARD_PRECISION, ROLE_ADMIN, ROLE_FREEZER, ROLE_KYC, ROLE_MINTER, ROLE_PAUSER,
+        ROLE_SNAPSHOT, TRANSFER_EVENT_SHORTNAME,
     };
 
     fn create_ctx(sender: Address) -> ContractContext {
@@ -30,6 +43,25 @@ mod test_contract {
         ctx
     }
 
+    fn contract_address() -> Address {
+        create_ctx(Address {
+            address_type: AddressType::Account,
+            identifier: [0u8; 20],
+        })
+        .contract_address
+    }
+
+    /// Decodes the arguments of a notification call, skipping the one byte shortname.
+    fn decode_transfer_event(payload: &[u8]) -> TransferEvent {
+        assert_eq!(TRANSFER_EVENT_SHORTNAME, payload[0] as u32);
+        TransferEvent::rpc_read_from(&mut &payload[1..])
+    }
+
+    fn decode_approval_event(payload: &[u8]) -> ApprovalEvent {
+        assert_eq!(APPROVAL_EVENT_SHORTNAME, payload[0] as u32);
+        ApprovalEvent::rpc_read_from(&mut &payload[1..])
+    }
+
     #[test]
     pub fn test_initialize() {
         let sender = Address {
@@ -43,10 +75,23 @@ mod test_contract {
             String::from("H$"),
             0,
             1000000,
+            None,
+            false,
+            100,
         );
         assert_eq!(0, events.len());
         assert_eq!(1000000, state.total_supply);
-        assert_eq!(sender, state.owner);
+        assert_eq!(None, state.max_supply);
+        assert!(!state.paused);
+        assert_eq!(Some(sender), state.owner);
+        assert_eq!(None, state.pending_owner);
+        assert!(state.has_role(ROLE_ADMIN, sender));
+        assert!(state.has_role(ROLE_MINTER, sender));
+        assert!(state.has_role(ROLE_PAUSER, sender));
+        assert!(state.has_role(ROLE_FREEZER, sender));
+        assert!(state.has_role(ROLE_KYC, sender));
+        assert!(state.has_role(ROLE_SNAPSHOT, sender));
+        assert!(!state.allowlist_enabled);
         assert_eq!(0, state.decimals);
         assert_eq!(String::from("HelloToken"), state.name);
         assert_eq!(String::from("H$"), state.symbol);
@@ -68,6 +113,9 @@ mod test_contract {
             String::from("H$"),
             0,
             1000000,
+            None,
+            false,
+            100,
         );
         let receiver = Address {
             address_type: AddressType::Account,
@@ -75,7 +123,8 @@ mod test_contract {
         };
         let ctx = create_ctx(sender);
         let (new_state, events) = transfer(ctx, state, receiver, 1000);
-        assert_eq!(0, events.len());
+        assert_eq!(1, events.len());
+        assert!(new_state.is_supply_consistent());
         assert_eq!(2, new_state.balances.len());
         assert_eq!(Some(&999000u128), new_state.balances.get(&sender));
         assert_eq!(Some(&1000u128), new_state.balances.get(&receiver));
@@ -94,24 +143,36 @@ mod test_contract {
             String::from("H$"),
             0,
             1000000,
+            None,
+            false,
+            100,
         );
         let receiver = sender;
         let ctx = create_ctx(sender);
         let (new_state, events) = transfer(ctx, state, receiver, 1000);
-        assert_eq!(0, events.len());
+        assert_eq!(1, events.len());
         assert_eq!(1, new_state.balances.len());
         assert_eq!(Some(&1000000u128), new_state.balances.get(&sender));
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "[TOKEN-001]")]
     pub fn test_transfer_invalid() {
         let sender = Address {
             address_type: AddressType::Account,
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
         };
         let ctx = create_ctx(sender);
-        let (state, _) = initialize(ctx, String::from("HelloToken"), String::from("H$"), 0, 999);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            999,
+            None,
+            false,
+            100,
+        );
         let receiver = Address {
             address_type: AddressType::Account,
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
@@ -121,7 +182,7 @@ mod test_contract {
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "[TOKEN-001]")]
     pub fn test_transfer_wrong_sender() {
         let sender = Address {
             address_type: AddressType::Account,
@@ -134,6 +195,9 @@ mod test_contract {
             String::from("H$"),
             0,
             1000000,
+            None,
+            false,
+            100,
         );
         let wrong_sender = Address {
             address_type: AddressType::Account,
@@ -154,17 +218,26 @@ mod test_contract {
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
         };
         let ctx = create_ctx(sender);
-        let (state, _) = initialize(ctx, String::from("HelloToken"), String::from("H$"), 0, 999);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            999,
+            None,
+            false,
+            100,
+        );
         let receiver = Address {
             address_type: AddressType::Account,
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
         };
         let ctx = create_ctx(sender);
         let (new_state, events) = transfer(ctx, state, receiver, 0);
-        assert_eq!(0, events.len());
-        assert_eq!(2, new_state.balances.len());
+        assert_eq!(1, events.len());
+        assert_eq!(1, new_state.balances.len());
         assert_eq!(Some(&999u128), new_state.balances.get(&sender));
-        assert_eq!(Some(&0u128), new_state.balances.get(&receiver));
+        assert_eq!(None, new_state.balances.get(&receiver));
     }
 
     #[test]
@@ -180,6 +253,9 @@ mod test_contract {
             String::from("H$"),
             0,
             1000000,
+            None,
+            false,
+            100,
         );
         let receiver1 = Address {
             address_type: AddressType::Account,
@@ -200,7 +276,7 @@ mod test_contract {
         };
         let transfers = vec![transfer1, transfer2];
         let (new_state, events) = bulk_transfer(ctx, state, transfers);
-        assert_eq!(0, events.len());
+        assert_eq!(1, events.len());
         assert_eq!(3, new_state.balances.len());
         assert_eq!(Some(&997000u128), new_state.balances.get(&sender));
         assert_eq!(Some(&1000u128), new_state.balances.get(&receiver1));
@@ -208,14 +284,23 @@ mod test_contract {
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "[TOKEN-001]")]
     pub fn test_bulk_transfer_invalid() {
         let sender = Address {
             address_type: AddressType::Account,
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
         };
         let ctx = create_ctx(sender);
-        let (state, _) = initialize(ctx, String::from("HelloToken"), String::from("H$"), 0, 1000);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
         let receiver1 = Address {
             address_type: AddressType::Account,
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
@@ -244,7 +329,16 @@ mod test_contract {
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
         };
         let ctx = create_ctx(sender);
-        let (state, _) = initialize(ctx, String::from("HelloToken"), String::from("H$"), 0, 1000);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
 
         assert_eq!(0, state.allowed.len());
         let ctx = create_ctx(sender);
@@ -252,13 +346,17 @@ mod test_contract {
             address_type: AddressType::Account,
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
         };
-        let (new_state, events) = approve(ctx, state, allowed_spender, 100);
+        let (new_state, events) = approve(ctx, state, allowed_spender, 100, None);
+        assert_eq!(1, events.len());
         assert_eq!(1, new_state.allowed.len());
         assert!(new_state.allowed.contains_key(&sender));
         let allowed_from_sender = new_state.allowed.get(&sender).unwrap();
         assert_eq!(1, allowed_from_sender.len());
         assert!(allowed_from_sender.contains_key(&allowed_spender));
-        assert_eq!(&100u128, allowed_from_sender.get(&allowed_spender).unwrap());
+        assert_eq!(
+            100u128,
+            allowed_from_sender.get(&allowed_spender).unwrap().amount
+        );
     }
 
     #[test]
@@ -268,7 +366,16 @@ mod test_contract {
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
         };
         let ctx = create_ctx(sender);
-        let (state, _) = initialize(ctx, String::from("HelloToken"), String::from("H$"), 0, 1000);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
 
         assert_eq!(0, state.allowed.len());
         let ctx = create_ctx(sender);
@@ -276,15 +383,18 @@ mod test_contract {
             address_type: AddressType::Account,
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
         };
-        let (intermediate_state, _) = approve(ctx, state, allowed_spender, 100);
+        let (intermediate_state, _) = approve(ctx, state, allowed_spender, 100, None);
         let ctx = create_ctx(sender);
-        let (new_state, _) = approve(ctx, intermediate_state, allowed_spender, 300);
+        let (new_state, _) = approve(ctx, intermediate_state, allowed_spender, 300, None);
         assert_eq!(1, new_state.allowed.len());
         assert!(new_state.allowed.contains_key(&sender));
         let allowed_from_sender = new_state.allowed.get(&sender).unwrap();
         assert_eq!(1, allowed_from_sender.len());
         assert!(allowed_from_sender.contains_key(&allowed_spender));
-        assert_eq!(&300u128, allowed_from_sender.get(&allowed_spender).unwrap());
+        assert_eq!(
+            300u128,
+            allowed_from_sender.get(&allowed_spender).unwrap().amount
+        );
     }
 
     #[test]
@@ -294,7 +404,16 @@ mod test_contract {
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
         };
         let ctx = create_ctx(sender);
-        let (state, _) = initialize(ctx, String::from("HelloToken"), String::from("H$"), 0, 1000);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
         let ctx = create_ctx(sender);
         let allowed_spender = Address {
             address_type: AddressType::Account,
@@ -304,16 +423,22 @@ mod test_contract {
             address_type: AddressType::Account,
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3],
         };
-        let (intermediate_state, _) = approve(ctx, state, allowed_spender, 100);
+        let (intermediate_state, _) = approve(ctx, state, allowed_spender, 100, None);
         let ctx = create_ctx(allowed_spender);
         let (new_state, events) = transfer_from(ctx, intermediate_state, sender, receiver, 100);
-        assert_eq!(1, new_state.allowed.len());
-        assert!(new_state.allowed.contains_key(&sender));
-        let allowed_from_sender = new_state.allowed.get(&sender).unwrap();
-        assert_eq!(1, allowed_from_sender.len());
-        assert!(allowed_from_sender.contains_key(&allowed_spender));
-        assert_eq!(&0u128, allowed_from_sender.get(&allowed_spender).unwrap());
-        assert_eq!(0, events.len());
+        assert!(new_state.allowed.is_empty());
+        assert_eq!(0, new_state.allowance(sender, allowed_spender, 123));
+        assert_eq!(1, events.len());
+        assert_eq!(2, events[0].events.len());
+        let event = decode_transfer_event(&events[0].events[0].payload);
+        assert_eq!(sender, event.from);
+        assert_eq!(receiver, event.to);
+        assert_eq!(allowed_spender, event.spender);
+        assert_eq!(100, event.amount);
+        let approval = decode_approval_event(&events[0].events[1].payload);
+        assert_eq!(sender, approval.owner);
+        assert_eq!(allowed_spender, approval.spender);
+        assert_eq!(0, approval.amount);
         assert_eq!(2, new_state.balances.len());
         assert_eq!(Some(&900u128), new_state.balances.get(&sender));
         assert_eq!(Some(&100u128), new_state.balances.get(&receiver));
@@ -326,7 +451,16 @@ mod test_contract {
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
         };
         let ctx = create_ctx(owner);
-        let (state, _) = initialize(ctx, String::from("HelloToken"), String::from("H$"), 0, 1000);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
         let allowed_spender = Address {
             address_type: AddressType::Account,
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
@@ -338,15 +472,10 @@ mod test_contract {
         let ctx = create_ctx(allowed_spender);
 
         let (new_state, _) = transfer_from(ctx, state, allowed_spender, receiver, 0);
-        assert_eq!(1, new_state.allowed.len());
-        assert!(new_state.allowed.contains_key(&allowed_spender));
-        let allowed_from_sender = new_state.allowed.get(&allowed_spender).unwrap();
-        assert_eq!(1, allowed_from_sender.len());
-        assert!(allowed_from_sender.contains_key(&allowed_spender));
-        assert_eq!(&0u128, allowed_from_sender.get(&allowed_spender).unwrap());
-        assert_eq!(2, new_state.balances.len());
+        assert!(new_state.allowed.is_empty());
+        assert_eq!(1, new_state.balances.len());
         assert_eq!(Some(&1000u128), new_state.balances.get(&owner));
-        assert_eq!(Some(&0u128), new_state.balances.get(&receiver));
+        assert_eq!(None, new_state.balances.get(&receiver));
     }
 
     #[test]
@@ -356,36 +485,50 @@ mod test_contract {
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
         };
         let ctx = create_ctx(sender);
-        let (state, _) = initialize(ctx, String::from("HelloToken"), String::from("H$"), 0, 1000);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
         let ctx = create_ctx(sender);
         let allowed_spender = Address {
             address_type: AddressType::Account,
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
         };
         let receiver = sender;
-        let (intermediate_state, _) = approve(ctx, state, allowed_spender, 100);
+        let (intermediate_state, _) = approve(ctx, state, allowed_spender, 100, None);
         let ctx = create_ctx(allowed_spender);
         let (new_state, events) = transfer_from(ctx, intermediate_state, sender, receiver, 100);
-        assert_eq!(1, new_state.allowed.len());
-        assert!(new_state.allowed.contains_key(&sender));
-        let allowed_from_sender = new_state.allowed.get(&sender).unwrap();
-        assert_eq!(1, allowed_from_sender.len());
-        assert!(allowed_from_sender.contains_key(&allowed_spender));
-        assert_eq!(&0u128, allowed_from_sender.get(&allowed_spender).unwrap());
-        assert_eq!(0, events.len());
+        assert!(new_state.allowed.is_empty());
+        assert_eq!(0, new_state.allowance(sender, allowed_spender, 123));
+        assert_eq!(1, events.len());
         assert_eq!(1, new_state.balances.len());
         assert_eq!(Some(&1000u128), new_state.balances.get(&sender));
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "[TOKEN-002]")]
     pub fn test_transfer_from_not_allowed() {
         let sender = Address {
             address_type: AddressType::Account,
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
         };
         let ctx = create_ctx(sender);
-        let (state, _) = initialize(ctx, String::from("HelloToken"), String::from("H$"), 0, 1000);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
         let ctx = create_ctx(sender);
         let allowed_spender = Address {
             address_type: AddressType::Account,
@@ -395,20 +538,29 @@ mod test_contract {
             address_type: AddressType::Account,
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3],
         };
-        let (intermediate_state, _) = approve(ctx, state, allowed_spender, 100);
+        let (intermediate_state, _) = approve(ctx, state, allowed_spender, 100, None);
         let ctx = create_ctx(allowed_spender);
         let (new_state, events) = transfer_from(ctx, intermediate_state, sender, receiver, 101);
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "[TOKEN-001]")]
     pub fn test_transfer_from_no_funds() {
         let sender = Address {
             address_type: AddressType::Account,
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
         };
         let ctx = create_ctx(sender);
-        let (state, _) = initialize(ctx, String::from("HelloToken"), String::from("H$"), 0, 100);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            100,
+            None,
+            false,
+            100,
+        );
         let ctx = create_ctx(sender);
         let allowed_spender = Address {
             address_type: AddressType::Account,
@@ -418,7 +570,7 @@ mod test_contract {
             address_type: AddressType::Account,
             identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3],
         };
-        let (intermediate_state, _) = approve(ctx, state, allowed_spender, 1000);
+        let (intermediate_state, _) = approve(ctx, state, allowed_spender, 1000, None);
         let ctx = create_ctx(allowed_spender);
         let (new_state, events) = transfer_from(ctx, intermediate_state, sender, receiver, 101);
     }
@@ -456,19 +608,25 @@ mod test_contract {
             .fold(0, |acc, to_and_amount| acc + to_and_amount.amount);
 
         let ctx = create_ctx(sender);
-        let (state, _) = initialize(ctx, String::from("HelloToken"), String::from("H$"), 0, 1000);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
         let ctx = create_ctx(sender);
         let (intermediate_state, _) =
-            approve(ctx, state, allowed_spender, total_amount_to_transfer);
+            approve(ctx, state, allowed_spender, total_amount_to_transfer, None);
         let ctx = create_ctx(allowed_spender);
         let (new_state, events) = bulk_transfer_from(ctx, intermediate_state, sender, transfers);
-        assert_eq!(1, new_state.allowed.len());
-        assert!(new_state.allowed.contains_key(&sender));
-        let allowed_from_sender = new_state.allowed.get(&sender).unwrap();
-        assert_eq!(1, allowed_from_sender.len());
-        assert!(allowed_from_sender.contains_key(&allowed_spender));
-        assert_eq!(&0u128, allowed_from_sender.get(&allowed_spender).unwrap());
-        assert_eq!(0, events.len());
+        assert!(new_state.allowed.is_empty());
+        assert_eq!(0, new_state.allowance(sender, allowed_spender, 123));
+        assert_eq!(1, events.len());
+        assert!(new_state.is_supply_consistent());
         assert_eq!(3, new_state.balances.len());
         assert_eq!(Some(&700u128), new_state.balances.get(&sender));
         assert_eq!(Some(&100u128), new_state.balances.get(&receiver1));
@@ -476,7 +634,7 @@ mod test_contract {
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "[TOKEN-002]")]
     pub fn test_bulk_transfer_not_allowed() {
         let sender = Address {
             address_type: AddressType::Account,
@@ -509,16 +667,30 @@ mod test_contract {
             .fold(0, |acc, to_and_amount| acc + to_and_amount.amount);
 
         let ctx = create_ctx(sender);
-        let (state, _) = initialize(ctx, String::from("HelloToken"), String::from("H$"), 0, 1000);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
         let ctx = create_ctx(sender);
-        let (intermediate_state, _) =
-            approve(ctx, state, allowed_spender, total_amount_to_transfer.sub(1));
+        let (intermediate_state, _) = approve(
+            ctx,
+            state,
+            allowed_spender,
+            total_amount_to_transfer.sub(1),
+            None,
+        );
         let ctx = create_ctx(allowed_spender);
         let (new_state, events) = bulk_transfer_from(ctx, intermediate_state, sender, transfers);
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "[TOKEN-001]")]
     pub fn test_bulk_transfer_from_no_funds() {
         let sender = Address {
             address_type: AddressType::Account,
@@ -551,10 +723,3636 @@ mod test_contract {
             .fold(0, |acc, to_and_amount| acc + to_and_amount.amount);
 
         let ctx = create_ctx(sender);
-        let (state, _) = initialize(ctx, String::from("HelloToken"), String::from("H$"), 0, 100);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            100,
+            None,
+            false,
+            100,
+        );
         let ctx = create_ctx(sender);
-        let (intermediate_state, _) = approve(ctx, state, allowed_spender, 1000);
+        let (intermediate_state, _) = approve(ctx, state, allowed_spender, 1000, None);
         let ctx = create_ctx(allowed_spender);
         let (new_state, events) = bulk_transfer_from(ctx, intermediate_state, sender, transfers);
     }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-006]")]
+    pub fn test_initialize_exceeds_max_supply() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let ctx = create_ctx(sender);
+        initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1001,
+            Some(1000),
+            false,
+            100,
+        );
+    }
+
+    #[test]
+    pub fn test_mint() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            Some(1500),
+            false,
+            100,
+        );
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (new_state, events) = mint(ctx, state, receiver, 500);
+        assert_eq!(1, events.len());
+        let event = decode_transfer_event(&events[0].events[0].payload);
+        assert_eq!(contract_address(), event.from);
+        assert_eq!(receiver, event.to);
+        assert_eq!(sender, event.spender);
+        assert_eq!(500, event.amount);
+        assert_eq!(1500, new_state.total_supply);
+        assert!(new_state.is_supply_consistent());
+        assert_eq!(2, new_state.balances.len());
+        assert_eq!(Some(&1000u128), new_state.balances.get(&sender));
+        assert_eq!(Some(&500u128), new_state.balances.get(&receiver));
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_mint_not_owner() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let not_owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(not_owner);
+        mint(ctx, state, not_owner, 500);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-006]")]
+    pub fn test_mint_exceeds_max_supply() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            Some(1500),
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        mint(ctx, state, sender, 501);
+    }
+
+    #[test]
+    pub fn test_burn() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (intermediate_state, events) = burn(ctx, state, 400);
+        assert_eq!(1, events.len());
+        let event = decode_transfer_event(&events[0].events[0].payload);
+        assert_eq!(sender, event.from);
+        assert_eq!(contract_address(), event.to);
+        assert_eq!(sender, event.spender);
+        assert_eq!(400, event.amount);
+        assert_eq!(600, intermediate_state.total_supply);
+        assert_eq!(Some(&600u128), intermediate_state.balances.get(&sender));
+        let ctx = create_ctx(sender);
+        let (new_state, _) = burn(ctx, intermediate_state, 600);
+        assert_eq!(0, new_state.total_supply);
+        assert!(new_state.balances.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-001]")]
+    pub fn test_burn_invalid() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        burn(ctx, state, 1001);
+    }
+
+    #[test]
+    pub fn test_burn_from() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let allowed_spender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (intermediate_state, _) = approve(ctx, state, allowed_spender, 300, None);
+        let ctx = create_ctx(allowed_spender);
+        let (new_state, events) = burn_from(ctx, intermediate_state, sender, 200);
+        assert_eq!(1, events.len());
+        assert_eq!(2, events[0].events.len());
+        let event = decode_transfer_event(&events[0].events[0].payload);
+        assert_eq!(sender, event.from);
+        assert_eq!(contract_address(), event.to);
+        assert_eq!(allowed_spender, event.spender);
+        assert_eq!(200, event.amount);
+        let approval = decode_approval_event(&events[0].events[1].payload);
+        assert_eq!(sender, approval.owner);
+        assert_eq!(allowed_spender, approval.spender);
+        assert_eq!(100, approval.amount);
+        assert_eq!(None, approval.expires_at);
+        assert_eq!(800, new_state.total_supply);
+        assert!(new_state.is_supply_consistent());
+        assert_eq!(Some(&800u128), new_state.balances.get(&sender));
+        let allowed_from_sender = new_state.allowed.get(&sender).unwrap();
+        assert_eq!(
+            100u128,
+            allowed_from_sender.get(&allowed_spender).unwrap().amount
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-002]")]
+    pub fn test_burn_from_not_allowed() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let allowed_spender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (intermediate_state, _) = approve(ctx, state, allowed_spender, 100, None);
+        let ctx = create_ctx(allowed_spender);
+        burn_from(ctx, intermediate_state, sender, 101);
+    }
+
+    #[test]
+    pub fn test_event_shortnames() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let allowed_spender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, _) = approve(ctx, state, allowed_spender, 300, None);
+        let ctx = create_ctx(allowed_spender);
+        let (_, events) = burn_from(ctx, state, sender, 200);
+        assert_eq!(0x20, TRANSFER_EVENT_SHORTNAME);
+        assert_eq!(0x21, APPROVAL_EVENT_SHORTNAME);
+        assert_eq!(contract_address(), events[0].events[0].dest);
+        assert_eq!(0x20, events[0].events[0].payload[0]);
+        assert_eq!(contract_address(), events[0].events[1].dest);
+        assert_eq!(0x21, events[0].events[1].payload[0]);
+    }
+
+    #[test]
+    pub fn test_transfer_event_from_contract() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let contract_address = create_ctx(sender).contract_address;
+        let ctx = create_ctx(contract_address);
+        let event = TransferEvent {
+            from: sender,
+            to: receiver,
+            spender: sender,
+            amount: 100,
+        };
+        let (new_state, events) = transfer_event(ctx, state, event);
+        assert_eq!(0, events.len());
+        assert_eq!(1, new_state.balances.len());
+        assert_eq!(Some(&1000u128), new_state.balances.get(&sender));
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_transfer_event_not_from_contract() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let event = TransferEvent {
+            from: sender,
+            to: receiver,
+            spender: sender,
+            amount: 100,
+        };
+        transfer_event(ctx, state, event);
+    }
+
+    #[test]
+    pub fn test_transfer_and_call() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::PublicContract,
+            identifier: [0u8, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (new_state, events) = transfer_and_call(ctx, state, receiver, 100, 0x42, vec![1, 2]);
+        assert_eq!(2, events.len());
+        let event = decode_transfer_event(&events[0].events[0].payload);
+        assert_eq!(sender, event.from);
+        assert_eq!(contract_address(), event.to);
+        assert_eq!(100, event.amount);
+        assert_eq!(1, events[1].events.len());
+        assert_eq!(receiver, events[1].events[0].dest);
+        assert_eq!(Some(&900u128), new_state.balances.get(&sender));
+        assert_eq!(None, new_state.balances.get(&receiver));
+        assert_eq!(100, new_state.pending_transfers);
+        assert!(new_state.is_supply_consistent());
+
+        let ctx = create_ctx(sender);
+        let callback_ctx = CallbackContext {
+            success: true,
+            results: vec![ExecutionResult {
+                succeeded: true,
+                return_data: vec![],
+            }],
+        };
+        let (new_state, events) =
+            transfer_and_call_callback(ctx, callback_ctx, new_state, sender, receiver, 100);
+        assert_eq!(1, events.len());
+        let event = decode_transfer_event(&events[0].events[0].payload);
+        assert_eq!(contract_address(), event.from);
+        assert_eq!(receiver, event.to);
+        assert_eq!(100, event.amount);
+        assert_eq!(Some(&900u128), new_state.balances.get(&sender));
+        assert_eq!(Some(&100u128), new_state.balances.get(&receiver));
+        assert_eq!(0, new_state.pending_transfers);
+        assert!(new_state.is_supply_consistent());
+    }
+
+    #[test]
+    pub fn test_transfer_and_call_rejected() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::PublicContract,
+            identifier: [0u8, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (intermediate_state, _) = transfer_and_call(ctx, state, receiver, 100, 0x42, vec![]);
+
+        let ctx = create_ctx(sender);
+        let callback_ctx = CallbackContext {
+            success: false,
+            results: vec![ExecutionResult {
+                succeeded: false,
+                return_data: vec![],
+            }],
+        };
+        let (new_state, events) = transfer_and_call_callback(
+            ctx,
+            callback_ctx,
+            intermediate_state,
+            sender,
+            receiver,
+            100,
+        );
+        assert_eq!(1, events.len());
+        let event = decode_transfer_event(&events[0].events[0].payload);
+        assert_eq!(contract_address(), event.from);
+        assert_eq!(sender, event.to);
+        assert_eq!(100, event.amount);
+        assert_eq!(1, new_state.balances.len());
+        assert_eq!(Some(&1000u128), new_state.balances.get(&sender));
+        assert_eq!(0, new_state.pending_transfers);
+        assert!(new_state.is_supply_consistent());
+    }
+
+    #[test]
+    pub fn test_transfer_and_call_ignores_other_results() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::PublicContract,
+            identifier: [0u8, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (intermediate_state, _) = transfer_and_call(ctx, state, receiver, 100, 0x42, vec![]);
+
+        let ctx = create_ctx(sender);
+        let callback_ctx = CallbackContext {
+            success: false,
+            results: vec![
+                ExecutionResult {
+                    succeeded: true,
+                    return_data: vec![],
+                },
+                ExecutionResult {
+                    succeeded: false,
+                    return_data: vec![],
+                },
+            ],
+        };
+        let (new_state, events) = transfer_and_call_callback(
+            ctx,
+            callback_ctx,
+            intermediate_state,
+            sender,
+            receiver,
+            100,
+        );
+        assert_eq!(1, events.len());
+        assert_eq!(Some(&900u128), new_state.balances.get(&sender));
+        assert_eq!(Some(&100u128), new_state.balances.get(&receiver));
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-007]")]
+    pub fn test_transfer_and_call_to_account() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        transfer_and_call(ctx, state, receiver, 100, 0x42, vec![]);
+    }
+
+    #[test]
+    pub fn test_error_codes() {
+        assert_eq!(1, TokenError::InsufficientBalance.code());
+        assert_eq!(2, TokenError::InsufficientAllowance.code());
+        assert_eq!(
+            "[TOKEN-005] Contract is paused",
+            format!("{}", TokenError::Paused)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-003]")]
+    pub fn test_transfer_overflow() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (mut state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            u128::MAX,
+            None,
+            false,
+            100,
+        );
+        state.balances.insert(receiver, 1);
+        assert!(!state.is_supply_consistent());
+        let ctx = create_ctx(sender);
+        transfer(ctx, state, receiver, u128::MAX);
+    }
+
+    #[test]
+    pub fn test_queries_do_not_mutate_state() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let unknown = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        assert_eq!(1000, state.balance_of(sender));
+        assert_eq!(0, state.balance_of(unknown));
+        assert_eq!(0, state.allowance(sender, unknown, 123));
+        assert_eq!(1, state.balances.len());
+        assert!(state.allowed.is_empty());
+    }
+
+    #[test]
+    pub fn test_approve_zero_removes_allowance() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let allowed_spender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (intermediate_state, _) = approve(ctx, state, allowed_spender, 100, None);
+        assert_eq!(
+            100,
+            intermediate_state.allowance(sender, allowed_spender, 123)
+        );
+        let ctx = create_ctx(sender);
+        let (new_state, _) = approve(ctx, intermediate_state, allowed_spender, 0, None);
+        assert!(new_state.allowed.is_empty());
+    }
+
+    #[test]
+    pub fn test_increase_and_decrease_allowance() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let allowed_spender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, events) = increase_allowance(ctx, state, allowed_spender, 100);
+        assert_eq!(1, events.len());
+        assert_eq!(100, state.allowance(sender, allowed_spender, 123));
+        let ctx = create_ctx(sender);
+        let (state, _) = increase_allowance(ctx, state, allowed_spender, 50);
+        assert_eq!(150, state.allowance(sender, allowed_spender, 123));
+        let ctx = create_ctx(sender);
+        let (state, events) = decrease_allowance(ctx, state, allowed_spender, 120, false);
+        assert_eq!(1, events.len());
+        assert_eq!(30, state.allowance(sender, allowed_spender, 123));
+        let ctx = create_ctx(sender);
+        let (new_state, _) = decrease_allowance(ctx, state, allowed_spender, 100, true);
+        assert_eq!(0, new_state.allowance(sender, allowed_spender, 123));
+        assert!(new_state.allowed.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-002]")]
+    pub fn test_decrease_allowance_below_zero() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let allowed_spender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, _) = approve(ctx, state, allowed_spender, 100, None);
+        let ctx = create_ctx(sender);
+        decrease_allowance(ctx, state, allowed_spender, 101, false);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-003]")]
+    pub fn test_increase_allowance_overflow() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let allowed_spender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, _) = approve(ctx, state, allowed_spender, u128::MAX, None);
+        let ctx = create_ctx(sender);
+        increase_allowance(ctx, state, allowed_spender, 1);
+    }
+
+    #[test]
+    pub fn test_safe_approve() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let allowed_spender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, _) = safe_approve(ctx, state, allowed_spender, 0, 100, None);
+        assert_eq!(100, state.allowance(sender, allowed_spender, 123));
+        let ctx = create_ctx(sender);
+        let (new_state, events) = safe_approve(ctx, state, allowed_spender, 100, 40, None);
+        assert_eq!(1, events.len());
+        assert_eq!(40, new_state.allowance(sender, allowed_spender, 123));
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-008]")]
+    pub fn test_safe_approve_mismatch() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let allowed_spender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, _) = approve(ctx, state, allowed_spender, 100, None);
+        let ctx = create_ctx(allowed_spender);
+        let (state, _) = transfer_from(ctx, state, sender, receiver, 60);
+        let ctx = create_ctx(sender);
+        safe_approve(ctx, state, allowed_spender, 100, 40, None);
+    }
+
+    #[test]
+    pub fn test_approve_with_expiry() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let allowed_spender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, _) = approve(ctx, state, allowed_spender, 100, Some(150));
+        assert_eq!(100, state.allowance(sender, allowed_spender, 150));
+        assert_eq!(
+            Some(150),
+            state.allowance_expiry(sender, allowed_spender, 150)
+        );
+        assert_eq!(0, state.allowance(sender, allowed_spender, 151));
+        assert_eq!(None, state.allowance_expiry(sender, allowed_spender, 151));
+
+        let ctx = create_ctx(allowed_spender);
+        let (new_state, _) = transfer_from(ctx, state, sender, receiver, 40);
+        assert_eq!(60, new_state.allowance(sender, allowed_spender, 123));
+        assert_eq!(
+            Some(150),
+            new_state.allowance_expiry(sender, allowed_spender, 123)
+        );
+    }
+
+    #[test]
+    pub fn test_safe_approve_renews_expired_allowance() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let allowed_spender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, _) = approve(ctx, state, allowed_spender, 100, Some(150));
+        let mut ctx = create_ctx(sender);
+        ctx.block_time = 151;
+        let (state, events) = safe_approve(ctx, state, allowed_spender, 0, 50, Some(300));
+        assert_eq!(50, state.allowance(sender, allowed_spender, 300));
+        assert_eq!(
+            Some(300),
+            state.allowance_expiry(sender, allowed_spender, 300)
+        );
+        assert_eq!(0, state.allowance(sender, allowed_spender, 301));
+        let approval = decode_approval_event(&events[0].events[0].payload);
+        assert_eq!(50, approval.amount);
+        assert_eq!(Some(300), approval.expires_at);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-026]")]
+    pub fn test_increase_expired_allowance() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let allowed_spender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, _) = approve(ctx, state, allowed_spender, 100, Some(150));
+        let mut ctx = create_ctx(sender);
+        ctx.block_time = 151;
+        increase_allowance(ctx, state, allowed_spender, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-002]")]
+    pub fn test_transfer_from_expired_allowance() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let allowed_spender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, _) = approve(ctx, state, allowed_spender, 100, Some(150));
+        let mut ctx = create_ctx(allowed_spender);
+        ctx.block_time = 151;
+        transfer_from(ctx, state, sender, receiver, 40);
+    }
+
+    #[test]
+    pub fn test_pause_and_unpause() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, _) = pause(ctx, state);
+        assert!(state.paused);
+        let ctx = create_ctx(sender);
+        let (state, _) = unpause(ctx, state);
+        assert!(!state.paused);
+        let ctx = create_ctx(sender);
+        let (new_state, _) = transfer(ctx, state, receiver, 100);
+        assert_eq!(Some(&100u128), new_state.balances.get(&receiver));
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-005]")]
+    pub fn test_transfer_paused() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, _) = pause(ctx, state);
+        let ctx = create_ctx(sender);
+        transfer(ctx, state, receiver, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-005]")]
+    pub fn test_mint_paused() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, _) = pause(ctx, state);
+        let ctx = create_ctx(sender);
+        mint(ctx, state, receiver, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-005]")]
+    pub fn test_transfer_from_paused() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let allowed_spender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, _) = approve(ctx, state, allowed_spender, 100, None);
+        let ctx = create_ctx(sender);
+        let (state, _) = pause(ctx, state);
+        let ctx = create_ctx(allowed_spender);
+        transfer_from(ctx, state, sender, allowed_spender, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_pause_not_owner() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let not_owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(not_owner);
+        pause(ctx, state);
+    }
+
+    #[test]
+    pub fn test_transfer_ownership() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let new_owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = propose_owner(ctx, state, new_owner);
+        assert_eq!(Some(owner), state.owner);
+        assert_eq!(Some(new_owner), state.pending_owner);
+        let ctx = create_ctx(new_owner);
+        let (state, _) = accept_ownership(ctx, state);
+        assert_eq!(Some(new_owner), state.owner);
+        assert_eq!(None, state.pending_owner);
+        assert!(state.has_role(ROLE_MINTER, new_owner));
+        assert!(!state.has_role(ROLE_ADMIN, owner));
+        assert!(!state.has_role(ROLE_MINTER, owner));
+        let ctx = create_ctx(new_owner);
+        let (new_state, _) = mint(ctx, state, new_owner, 100);
+        assert_eq!(Some(&100u128), new_state.balances.get(&new_owner));
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_transfer_ownership_old_owner_mint() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let new_owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = propose_owner(ctx, state, new_owner);
+        let ctx = create_ctx(new_owner);
+        let (state, _) = accept_ownership(ctx, state);
+        let ctx = create_ctx(owner);
+        mint(ctx, state, owner, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_accept_ownership_not_pending() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let new_owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let other = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = propose_owner(ctx, state, new_owner);
+        let ctx = create_ctx(other);
+        accept_ownership(ctx, state);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_renounce_ownership() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = renounce_ownership(ctx, state);
+        assert_eq!(None, state.owner);
+        assert!(!state.has_role(ROLE_ADMIN, owner));
+        let ctx = create_ctx(owner);
+        pause(ctx, state);
+    }
+
+    #[test]
+    pub fn test_grant_and_revoke_role() {
+        let admin = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let minter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(admin);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(admin);
+        let (state, _) = grant_role(ctx, state, ROLE_MINTER, minter);
+        assert!(state.has_role(ROLE_MINTER, minter));
+        assert!(!state.has_role(ROLE_PAUSER, minter));
+        let ctx = create_ctx(minter);
+        let (state, _) = mint(ctx, state, minter, 500);
+        assert_eq!(Some(&500u128), state.balances.get(&minter));
+        let ctx = create_ctx(admin);
+        let (state, _) = revoke_role(ctx, state, ROLE_MINTER, minter);
+        assert!(!state.has_role(ROLE_MINTER, minter));
+        let ctx = create_ctx(admin);
+        let (new_state, _) = renounce_role(ctx, state, ROLE_MINTER);
+        assert!(!new_state.has_role(ROLE_MINTER, admin));
+        assert!(new_state.has_role(ROLE_ADMIN, admin));
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_grant_role_not_admin() {
+        let admin = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let minter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(admin);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(admin);
+        let (state, _) = grant_role(ctx, state, ROLE_MINTER, minter);
+        let ctx = create_ctx(minter);
+        grant_role(ctx, state, ROLE_PAUSER, minter);
+    }
+
+    #[test]
+    pub fn test_set_role_admin() {
+        let admin = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let pauser = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(admin);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(admin);
+        let (state, _) = set_role_admin(ctx, state, ROLE_PAUSER, ROLE_PAUSER);
+        assert_eq!(ROLE_PAUSER, state.role_admin(ROLE_PAUSER));
+        let ctx = create_ctx(admin);
+        let (new_state, _) = grant_role(ctx, state, ROLE_PAUSER, pauser);
+        assert!(new_state.has_role(ROLE_PAUSER, pauser));
+    }
+
+    #[test]
+    pub fn test_freeze_and_unfreeze() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, _) = freeze(ctx, state, receiver);
+        assert!(state.is_frozen(receiver));
+        let ctx = create_ctx(sender);
+        let (state, _) = unfreeze(ctx, state, receiver);
+        assert!(!state.is_frozen(receiver));
+        let ctx = create_ctx(sender);
+        let (new_state, _) = transfer(ctx, state, receiver, 100);
+        assert_eq!(Some(&100u128), new_state.balances.get(&receiver));
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-009]")]
+    pub fn test_transfer_to_frozen() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, _) = freeze(ctx, state, receiver);
+        let ctx = create_ctx(sender);
+        transfer(ctx, state, receiver, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-009]")]
+    pub fn test_transfer_from_frozen_owner() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let allowed_spender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(sender);
+        let (state, _) = approve(ctx, state, allowed_spender, 100, None);
+        let ctx = create_ctx(sender);
+        let (state, _) = freeze(ctx, state, sender);
+        let ctx = create_ctx(allowed_spender);
+        transfer_from(ctx, state, sender, receiver, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_freeze_not_freezer() {
+        let sender = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let other = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(sender);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(other);
+        freeze(ctx, state, sender);
+    }
+
+    #[test]
+    pub fn test_allowlist_mode() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            true,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = add_to_allowlist(ctx, state, vec![holder]);
+        assert!(state.is_allowlisted(holder));
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 300);
+        assert_eq!(Some(&300u128), state.balances.get(&holder));
+        let ctx = create_ctx(owner);
+        let (state, _) = remove_from_allowlist(ctx, state, vec![holder]);
+        assert!(!state.is_allowlisted(holder));
+        let ctx = create_ctx(holder);
+        let (new_state, _) = transfer(ctx, state, owner, 300);
+        assert_eq!(Some(&1000u128), new_state.balances.get(&owner));
+        assert_eq!(None, new_state.balances.get(&holder));
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-010]")]
+    pub fn test_allowlist_mode_transfer_not_allowlisted() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            true,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        transfer(ctx, state, holder, 300);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_add_to_allowlist_not_kyc() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            true,
+            100,
+        );
+        let ctx = create_ctx(holder);
+        add_to_allowlist(ctx, state, vec![holder]);
+    }
+
+    #[test]
+    pub fn test_vesting_release() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let beneficiary = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = create_vesting(ctx, state, beneficiary, 400, 100, 100, 400, false);
+        assert_eq!(Some(&600u128), state.balances.get(&owner));
+        assert_eq!(1, state.next_vesting_id);
+        assert!(state.is_supply_consistent());
+        assert_eq!(0, state.releasable_amount(0, 150));
+        assert_eq!(150, state.releasable_amount(0, 250));
+
+        let mut ctx = create_ctx(beneficiary);
+        ctx.block_time = 300;
+        let (state, _) = release(ctx, state, 0);
+        assert_eq!(Some(&200u128), state.balances.get(&beneficiary));
+        assert_eq!(0, state.releasable_amount(0, 300));
+        assert!(state.is_supply_consistent());
+
+        let mut ctx = create_ctx(beneficiary);
+        ctx.block_time = 600;
+        let (state, _) = release(ctx, state, 0);
+        assert_eq!(Some(&400u128), state.balances.get(&beneficiary));
+        assert!(state.vesting_schedules.is_empty());
+        assert!(state.is_supply_consistent());
+    }
+
+    #[test]
+    pub fn test_vesting_revoke() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let beneficiary = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = create_vesting(ctx, state, beneficiary, 400, 100, 100, 400, true);
+        let mut ctx = create_ctx(owner);
+        ctx.block_time = 300;
+        let (state, _) = revoke(ctx, state, 0);
+        assert_eq!(Some(&800u128), state.balances.get(&owner));
+        assert_eq!(200, state.vested_amount(0, 1000));
+        assert!(state.is_supply_consistent());
+
+        let mut ctx = create_ctx(beneficiary);
+        ctx.block_time = 1000;
+        let (state, _) = release(ctx, state, 0);
+        assert_eq!(Some(&200u128), state.balances.get(&beneficiary));
+        assert!(state.vesting_schedules.is_empty());
+        assert!(state.is_supply_consistent());
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_vesting_revoke_not_revocable() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let beneficiary = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = create_vesting(ctx, state, beneficiary, 400, 100, 100, 400, false);
+        let ctx = create_ctx(owner);
+        revoke(ctx, state, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_vesting_release_not_beneficiary() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let beneficiary = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = create_vesting(ctx, state, beneficiary, 400, 100, 100, 400, false);
+        let mut ctx = create_ctx(owner);
+        ctx.block_time = 600;
+        release(ctx, state, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-012]")]
+    pub fn test_vesting_cliff_after_duration() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let beneficiary = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        create_vesting(ctx, state, beneficiary, 400, 100, 500, 400, false);
+    }
+
+    #[test]
+    pub fn test_lock() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = lock(ctx, state, 600, 200);
+        assert_eq!(600, state.locked_balance(owner, 123));
+        assert_eq!(400, state.spendable_balance(owner, 123));
+        assert_eq!(1000, state.spendable_balance(owner, 200));
+
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, receiver, 400);
+        assert_eq!(Some(&600u128), state.balances.get(&owner));
+
+        let mut ctx = create_ctx(owner);
+        ctx.block_time = 200;
+        let (state, _) = transfer(ctx, state, receiver, 600);
+        assert_eq!(None, state.balances.get(&owner));
+        assert!(state.locks.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-013]")]
+    pub fn test_lock_transfer_locked() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = lock(ctx, state, 600, 200);
+        let ctx = create_ctx(owner);
+        transfer(ctx, state, receiver, 401);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-013]")]
+    pub fn test_lock_burn_locked() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = lock(ctx, state, 600, 200);
+        let ctx = create_ctx(owner);
+        burn(ctx, state, 401);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-013]")]
+    pub fn test_lock_more_than_unlocked() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = lock(ctx, state, 600, 200);
+        let ctx = create_ctx(owner);
+        lock(ctx, state, 401, 300);
+    }
+
+    #[test]
+    pub fn test_snapshot() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = snapshot(ctx, state);
+        assert_eq!(1, state.current_snapshot_id);
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, receiver, 300);
+        let ctx = create_ctx(owner);
+        let (state, _) = snapshot(ctx, state);
+        let ctx = create_ctx(owner);
+        let (state, _) = burn(ctx, state, 200);
+        let ctx = create_ctx(receiver);
+        let (state, _) = transfer(ctx, state, owner, 100);
+
+        assert_eq!(1000, state.balance_of_at(owner, 1));
+        assert_eq!(0, state.balance_of_at(receiver, 1));
+        assert_eq!(1000, state.total_supply_at(1));
+        assert_eq!(700, state.balance_of_at(owner, 2));
+        assert_eq!(300, state.balance_of_at(receiver, 2));
+        assert_eq!(1000, state.total_supply_at(2));
+
+        let ctx = create_ctx(owner);
+        let (state, _) = snapshot(ctx, state);
+        assert_eq!(600, state.balance_of_at(owner, 3));
+        assert_eq!(200, state.balance_of_at(receiver, 3));
+        assert_eq!(800, state.total_supply_at(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-014]")]
+    pub fn test_balance_of_at_unknown_snapshot() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = snapshot(ctx, state);
+        state.balance_of_at(owner, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_snapshot_not_snapshot_role() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(receiver);
+        snapshot(ctx, state);
+    }
+
+    #[test]
+    pub fn test_request_balance_at() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let requester = Address {
+            address_type: AddressType::PublicContract,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = snapshot(ctx, state);
+        let ctx = create_ctx(requester);
+        let (_, events) = request_balance_at(ctx, state, receiver, 1, 0x30);
+        assert_eq!(1, events.len());
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-007]")]
+    pub fn test_request_balance_at_from_account() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = snapshot(ctx, state);
+        let ctx = create_ctx(receiver);
+        request_balance_at(ctx, state, receiver, 1, 0x30);
+    }
+
+    #[test]
+    pub fn test_delegate() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = delegate(ctx, state, owner);
+        assert_eq!(Some(owner), state.delegates(owner));
+        assert_eq!(1000, state.get_votes(owner));
+
+        let mut ctx = create_ctx(owner);
+        ctx.block_time = 200;
+        let (state, _) = transfer(ctx, state, receiver, 300);
+        assert_eq!(700, state.get_votes(owner));
+        assert_eq!(0, state.get_votes(receiver));
+
+        let mut ctx = create_ctx(receiver);
+        ctx.block_time = 300;
+        let (state, _) = delegate(ctx, state, owner);
+        assert_eq!(1000, state.get_votes(owner));
+
+        let mut ctx = create_ctx(receiver);
+        ctx.block_time = 400;
+        let (state, _) = delegate(ctx, state, receiver);
+        assert_eq!(700, state.get_votes(owner));
+        assert_eq!(300, state.get_votes(receiver));
+
+        assert_eq!(0, state.get_past_votes(owner, 122, 401));
+        assert_eq!(1000, state.get_past_votes(owner, 123, 401));
+        assert_eq!(700, state.get_past_votes(owner, 250, 401));
+        assert_eq!(1000, state.get_past_votes(owner, 300, 401));
+        assert_eq!(700, state.get_past_votes(owner, 400, 401));
+        assert_eq!(0, state.get_past_votes(receiver, 399, 401));
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-022]")]
+    pub fn test_get_past_votes_future() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = delegate(ctx, state, owner);
+        state.get_past_votes(owner, 124, 123);
+    }
+
+    #[test]
+    pub fn test_delegate_mint_and_burn() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = delegate(ctx, state, receiver);
+        let ctx = create_ctx(owner);
+        let (state, _) = mint(ctx, state, owner, 500);
+        assert_eq!(1500, state.get_votes(receiver));
+        let ctx = create_ctx(owner);
+        let (state, _) = burn(ctx, state, 200);
+        assert_eq!(1300, state.get_votes(receiver));
+        assert_eq!(1, state.vote_checkpoints.get(&receiver).unwrap().len());
+    }
+
+    #[test]
+    pub fn test_request_past_votes() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let governor = Address {
+            address_type: AddressType::PublicContract,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = delegate(ctx, state, owner);
+        let mut ctx = create_ctx(governor);
+        ctx.block_time = 200;
+        let (_, events) = request_past_votes(ctx, state, owner, 123, 0x30);
+        assert_eq!(1, events.len());
+        let mut payload = &events[0].events[0].payload[1..];
+        assert_eq!(owner, Address::rpc_read_from(&mut payload));
+        assert_eq!(123, i64::rpc_read_from(&mut payload));
+        assert_eq!(1000, u128::rpc_read_from(&mut payload));
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-022]")]
+    pub fn test_request_past_votes_current_time() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let governor = Address {
+            address_type: AddressType::PublicContract,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = delegate(ctx, state, owner);
+        let ctx = create_ctx(governor);
+        request_past_votes(ctx, state, owner, 123, 0x30);
+    }
+
+    #[test]
+    pub fn test_proposal_mint() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let voter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, voter, 400);
+        let ctx = create_ctx(voter);
+        let (state, _) = create_proposal(
+            ctx,
+            state,
+            String::from("Mint to voter"),
+            ProposalAction::Mint {
+                to: voter,
+                amount: 500,
+            },
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = cast_vote(ctx, state, 0, VoteType::Against {});
+        // Tokens received after the proposal was created do not add voting weight.
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, voter, 300);
+        let ctx = create_ctx(voter);
+        let (state, _) = cast_vote(ctx, state, 0, VoteType::For {});
+        let proposal = state.proposals.get(&0).unwrap();
+        assert_eq!(400, proposal.for_votes);
+        assert_eq!(600, proposal.against_votes);
+
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        let (state, _) = finalize_proposal(ctx, state, 0);
+        assert_eq!(
+            ProposalStatus::Defeated {},
+            state.proposals.get(&0).unwrap().status
+        );
+    }
+
+    #[test]
+    pub fn test_proposal_execute() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let voter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, voter, 400);
+        let ctx = create_ctx(voter);
+        let (state, _) =
+            create_proposal(ctx, state, String::from("Pause"), ProposalAction::Pause {});
+        let ctx = create_ctx(voter);
+        let (state, _) = cast_vote(ctx, state, 0, VoteType::For {});
+        let ctx = create_ctx(owner);
+        let (state, _) = cast_vote(ctx, state, 0, VoteType::Abstain {});
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        let (state, _) = finalize_proposal(ctx, state, 0);
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        let (state, _) = execute_proposal(ctx, state, 0);
+        assert!(state.paused);
+        assert_eq!(
+            ProposalStatus::Executed {},
+            state.proposals.get(&0).unwrap().status
+        );
+    }
+
+    #[test]
+    pub fn test_proposal_execute_mint() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let voter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, voter, 400);
+        let ctx = create_ctx(voter);
+        let (state, _) = create_proposal(
+            ctx,
+            state,
+            String::from("Mint to voter"),
+            ProposalAction::Mint {
+                to: voter,
+                amount: 500,
+            },
+        );
+        let ctx = create_ctx(voter);
+        let (state, _) = cast_vote(ctx, state, 0, VoteType::For {});
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        let (state, _) = finalize_proposal(ctx, state, 0);
+        let mut ctx = create_ctx(owner);
+        ctx.block_time = 224;
+        let (state, events) = execute_proposal(ctx, state, 0);
+        assert_eq!(1500, state.total_supply);
+        assert_eq!(Some(&900u128), state.balances.get(&voter));
+        assert_eq!(1, events.len());
+        let event = decode_transfer_event(&events[0].events[0].payload);
+        assert_eq!(contract_address(), event.from);
+        assert_eq!(voter, event.to);
+        assert_eq!(owner, event.spender);
+        assert_eq!(500, event.amount);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_proposal_execute_after_renounce_ownership() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let voter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, voter, 400);
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 123;
+        let (state, _) =
+            create_proposal(ctx, state, String::from("Pause"), ProposalAction::Pause {});
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 123;
+        let (state, _) = cast_vote(ctx, state, 0, VoteType::For {});
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        let (state, _) = finalize_proposal(ctx, state, 0);
+        let ctx = create_ctx(owner);
+        let (state, _) = renounce_ownership(ctx, state);
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        execute_proposal(ctx, state, 0);
+    }
+
+    #[test]
+    pub fn test_proposal_unpause_after_proposal_pause() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let voter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, voter, 400);
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 123;
+        let (state, _) =
+            create_proposal(ctx, state, String::from("Pause"), ProposalAction::Pause {});
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 123;
+        let (state, _) = cast_vote(ctx, state, 0, VoteType::For {});
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        let (state, _) = finalize_proposal(ctx, state, 0);
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        let (state, _) = execute_proposal(ctx, state, 0);
+        assert!(state.paused);
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        let (state, _) = create_proposal(
+            ctx,
+            state,
+            String::from("Unpause"),
+            ProposalAction::Unpause {},
+        );
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        let (state, _) = cast_vote(ctx, state, 1, VoteType::For {});
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 325;
+        let (state, _) = finalize_proposal(ctx, state, 1);
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 325;
+        let (state, _) = execute_proposal(ctx, state, 1);
+        assert!(!state.paused);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_proposal_unpause_after_pauser_pause() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let voter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, voter, 400);
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 123;
+        let (state, _) = create_proposal(
+            ctx,
+            state,
+            String::from("Unpause"),
+            ProposalAction::Unpause {},
+        );
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 123;
+        let (state, _) = cast_vote(ctx, state, 0, VoteType::For {});
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        let (state, _) = finalize_proposal(ctx, state, 0);
+        let ctx = create_ctx(owner);
+        let (state, _) = pause(ctx, state);
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        execute_proposal(ctx, state, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-016]")]
+    pub fn test_cast_vote_after_voting_period() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let voter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, voter, 400);
+        let ctx = create_ctx(voter);
+        let (state, _) =
+            create_proposal(ctx, state, String::from("Pause"), ProposalAction::Pause {});
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        cast_vote(ctx, state, 0, VoteType::For {});
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-017]")]
+    pub fn test_finalize_proposal_before_end() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let voter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, voter, 400);
+        let ctx = create_ctx(voter);
+        let (state, _) =
+            create_proposal(ctx, state, String::from("Pause"), ProposalAction::Pause {});
+        let ctx = create_ctx(voter);
+        finalize_proposal(ctx, state, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-027]")]
+    pub fn test_finalize_proposal_twice() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let voter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, voter, 400);
+        let ctx = create_ctx(voter);
+        let (state, _) =
+            create_proposal(ctx, state, String::from("Pause"), ProposalAction::Pause {});
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        let (state, _) = finalize_proposal(ctx, state, 0);
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 225;
+        finalize_proposal(ctx, state, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-018]")]
+    pub fn test_cast_vote_twice() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let voter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, voter, 400);
+        let ctx = create_ctx(voter);
+        let (state, _) =
+            create_proposal(ctx, state, String::from("Pause"), ProposalAction::Pause {});
+        let ctx = create_ctx(voter);
+        let (state, _) = cast_vote(ctx, state, 0, VoteType::For {});
+        let ctx = create_ctx(voter);
+        cast_vote(ctx, state, 0, VoteType::Against {});
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-019]")]
+    pub fn test_execute_defeated_proposal() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let voter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, voter, 400);
+        let ctx = create_ctx(voter);
+        let (state, _) =
+            create_proposal(ctx, state, String::from("Pause"), ProposalAction::Pause {});
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        let (state, _) = finalize_proposal(ctx, state, 0);
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        execute_proposal(ctx, state, 0);
+    }
+
+    #[test]
+    pub fn test_proposal_quorum_not_reached() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let voter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, voter, 30);
+        let ctx = create_ctx(voter);
+        let (state, _) =
+            create_proposal(ctx, state, String::from("Pause"), ProposalAction::Pause {});
+        assert_eq!(40, state.proposals.get(&0).unwrap().quorum);
+        let ctx = create_ctx(voter);
+        let (state, _) = cast_vote(ctx, state, 0, VoteType::For {});
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        let (state, _) = finalize_proposal(ctx, state, 0);
+        assert_eq!(
+            ProposalStatus::Defeated {},
+            state.proposals.get(&0).unwrap().status
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_create_proposal_below_threshold() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let voter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, voter, 99);
+        let ctx = create_ctx(owner);
+        let (state, _) = set_governance_parameters(ctx, state, 100, 400, 1000);
+        assert_eq!(1000, state.proposal_threshold_bps);
+        let ctx = create_ctx(voter);
+        create_proposal(ctx, state, String::from("Pause"), ProposalAction::Pause {});
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_create_proposal_below_default_threshold() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let voter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, voter, 9);
+        let ctx = create_ctx(voter);
+        create_proposal(ctx, state, String::from("Pause"), ProposalAction::Pause {});
+    }
+
+    #[test]
+    pub fn test_proposal_quorum_excludes_staked() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let voter = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, voter, 30);
+        let ctx = create_ctx(owner);
+        let (state, _) = stake(ctx, state, 500);
+        let ctx = create_ctx(voter);
+        let (state, _) =
+            create_proposal(ctx, state, String::from("Pause"), ProposalAction::Pause {});
+        assert_eq!(20, state.proposals.get(&0).unwrap().quorum);
+        let ctx = create_ctx(voter);
+        let (state, _) = cast_vote(ctx, state, 0, VoteType::For {});
+        let mut ctx = create_ctx(voter);
+        ctx.block_time = 224;
+        let (state, _) = finalize_proposal(ctx, state, 0);
+        assert_eq!(
+            ProposalStatus::Succeeded {},
+            state.proposals.get(&0).unwrap().status
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-024]")]
+    pub fn test_create_proposal_description_too_long() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        create_proposal(
+            ctx,
+            state,
+            "x".repeat(MAX_DESCRIPTION_LENGTH + 1),
+            ProposalAction::Pause {},
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-025]")]
+    pub fn test_create_proposal_already_active() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) =
+            create_proposal(ctx, state, String::from("Pause"), ProposalAction::Pause {});
+        let mut ctx = create_ctx(owner);
+        ctx.block_time = 223;
+        create_proposal(ctx, state, String::from("Pause"), ProposalAction::Pause {});
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-003]")]
+    pub fn test_create_proposal_end_overflow() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let mut ctx = create_ctx(owner);
+        ctx.block_time = i64::MAX - 50;
+        create_proposal(ctx, state, String::from("Pause"), ProposalAction::Pause {});
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-023]")]
+    pub fn test_initialize_invalid_voting_period() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let ctx = create_ctx(owner);
+        initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            -1,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-023]")]
+    pub fn test_set_governance_parameters_invalid_quorum() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        set_governance_parameters(ctx, state, 100, 10_001, 100);
+    }
+
+    #[test]
+    pub fn test_rewards() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 250);
+        let ctx = create_ctx(owner);
+        let (state, _) = deposit_rewards(ctx, state, 300);
+        assert_eq!(Some(&450u128), state.balances.get(&owner));
+        assert_eq!(300, state.reward_pool);
+        assert_eq!(107, state.claimable_rewards(holder));
+        assert_eq!(192, state.claimable_rewards(owner));
+        assert!(state.is_supply_consistent());
+
+        let ctx = create_ctx(holder);
+        let (state, _) = transfer(ctx, state, owner, 50);
+        assert_eq!(107, state.claimable_rewards(holder));
+        assert_eq!(192, state.claimable_rewards(owner));
+
+        let ctx = create_ctx(holder);
+        let (state, _) = claim_rewards(ctx, state);
+        assert_eq!(Some(&307u128), state.balances.get(&holder));
+        assert_eq!(0, state.claimable_rewards(holder));
+        let ctx = create_ctx(owner);
+        let (state, _) = claim_rewards(ctx, state);
+        assert_eq!(Some(&692u128), state.balances.get(&owner));
+        assert_eq!(1, state.reward_pool);
+        assert!(state.is_supply_consistent());
+    }
+
+    #[test]
+    pub fn test_rewards_never_exceed_deposits() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 250);
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 650);
+        let ctx = create_ctx(owner);
+        let (state, _) = deposit_rewards(ctx, state, 1);
+        let ctx = create_ctx(owner);
+        let (state, _) = deposit_rewards(ctx, state, 1);
+        let ctx = create_ctx(owner);
+        let (state, _) = deposit_rewards(ctx, state, 1);
+        let claimable = state.claimable_rewards(owner) + state.claimable_rewards(holder);
+        assert!(claimable <= state.reward_pool);
+        assert_eq!(2, state.claimable_rewards(holder));
+    }
+
+    #[test]
+    pub fn test_reward_for() {
+        assert_eq!(u128::MAX / 2, reward_for(u128::MAX / 2, REWARD_PRECISION));
+        assert_eq!(
+            1_500_000_000_000_000_000_000_000_000,
+            reward_for(3_000_000_000_000_000_000_000_000_000, REWARD_PRECISION / 2)
+        );
+        assert_eq!(0, reward_for(1, REWARD_PRECISION - 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-003]")]
+    pub fn test_deposit_rewards_overflow() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let deposit = 300_000_000_000_000_000_000_000_000u128;
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            deposit + 1,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 1);
+        // The holder is the only eligible holder, so each deposit adds `deposit` per token.
+        let ctx = create_ctx(owner);
+        let (state, _) = deposit_rewards(ctx, state, deposit);
+        assert_eq!(deposit * REWARD_PRECISION, state.reward_per_token);
+        let ctx = create_ctx(owner);
+        let (state, _) = mint(ctx, state, owner, deposit);
+        let ctx = create_ctx(owner);
+        deposit_rewards(ctx, state, deposit);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_deposit_rewards_not_owner() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 250);
+        let ctx = create_ctx(holder);
+        deposit_rewards(ctx, state, 100);
+    }
+
+    #[test]
+    pub fn test_staking() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 250);
+        let ctx = create_ctx(owner);
+        let (state, _) = set_staking_parameters(ctx, state, 2, 50);
+        let ctx = create_ctx(owner);
+        let (state, _) = fund_staking_reserve(ctx, state, 150);
+        let ctx = create_ctx(holder);
+        let (state, _) = stake(ctx, state, 100);
+        let ctx = create_ctx(owner);
+        let (state, _) = stake(ctx, state, 300);
+        assert_eq!(Some(&150u128), state.balances.get(&holder));
+        assert_eq!(100, state.staked_balance(holder));
+        assert_eq!(400, state.staking.total_staked);
+        assert!(state.is_supply_consistent());
+
+        assert_eq!(25, state.staking_rewards(holder, 173));
+        assert_eq!(75, state.staking_rewards(owner, 173));
+
+        let mut ctx = create_ctx(holder);
+        ctx.block_time = 173;
+        let (state, _) = claim(ctx, state);
+        assert_eq!(Some(&175u128), state.balances.get(&holder));
+        assert_eq!(0, state.staking_rewards(holder, 173));
+        assert!(state.is_supply_consistent());
+
+        // The reserve runs out after another 50 rewards.
+        assert_eq!(75 + 37, state.staking_rewards(owner, 1000));
+        assert_eq!(12, state.staking_rewards(holder, 1000));
+    }
+
+    #[test]
+    pub fn test_unstake_unbonding() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 250);
+        let ctx = create_ctx(owner);
+        let (state, _) = set_staking_parameters(ctx, state, 0, 50);
+        let ctx = create_ctx(holder);
+        let (state, _) = stake(ctx, state, 100);
+        let ctx = create_ctx(holder);
+        let (state, _) = unstake(ctx, state, 100);
+        assert_eq!(Some(&250u128), state.balances.get(&holder));
+        assert_eq!(0, state.staked_balance(holder));
+        assert_eq!(150, state.spendable_balance(holder, 123));
+        assert_eq!(250, state.spendable_balance(holder, 173));
+        assert!(state.staking.stakes.is_empty());
+        assert!(state.is_supply_consistent());
+    }
+
+    #[test]
+    pub fn test_unstake_unbonding_saturates() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 250);
+        let ctx = create_ctx(owner);
+        let (state, _) = set_staking_parameters(ctx, state, 0, i64::MAX);
+        let ctx = create_ctx(holder);
+        let (state, _) = stake(ctx, state, 100);
+        let ctx = create_ctx(holder);
+        let (state, _) = unstake(ctx, state, 100);
+        assert_eq!(Some(&250u128), state.balances.get(&holder));
+        assert_eq!(150, state.spendable_balance(holder, i64::MAX - 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-003]")]
+    pub fn test_staking_reward_per_token_overflow() {
+        let deposit = 300_000_000_000_000_000_000_000_000u128;
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            deposit * 2 + 2,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 1);
+        let ctx = create_ctx(holder);
+        let (state, _) = stake(ctx, state, 1);
+        let ctx = create_ctx(owner);
+        let (state, _) = set_staking_parameters(ctx, state, u128::MAX, 0);
+        let ctx = create_ctx(owner);
+        let (state, _) = fund_staking_reserve(ctx, state, deposit);
+        // The whole reserve accrues to the single staked token.
+        let mut ctx = create_ctx(owner);
+        ctx.block_time = 124;
+        let (state, _) = fund_staking_reserve(ctx, state, deposit);
+        assert_eq!(deposit * REWARD_PRECISION, state.staking.reward_per_token);
+        let mut ctx = create_ctx(owner);
+        ctx.block_time = 125;
+        fund_staking_reserve(ctx, state, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-001]")]
+    pub fn test_unstake_more_than_staked() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 250);
+        let ctx = create_ctx(holder);
+        let (state, _) = stake(ctx, state, 100);
+        let ctx = create_ctx(holder);
+        unstake(ctx, state, 101);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-004]")]
+    pub fn test_set_staking_parameters_not_owner() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 250);
+        let ctx = create_ctx(holder);
+        set_staking_parameters(ctx, state, 2, 50);
+    }
+
+    #[test]
+    pub fn test_transfer_fee() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3],
+        };
+        let treasury = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = set_fee_config(
+            ctx,
+            state,
+            Some(FeeConfig {
+                rate_bps: 100,
+                recipient: treasury,
+            }),
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 500);
+        assert_eq!(Some(&500u128), state.balances.get(&holder));
+        assert_eq!(None, state.balances.get(&treasury));
+
+        let ctx = create_ctx(holder);
+        let (state, events) = transfer(ctx, state, receiver, 200);
+        assert_eq!(1, events.len());
+        assert_eq!(Some(&300u128), state.balances.get(&holder));
+        assert_eq!(Some(&198u128), state.balances.get(&receiver));
+        assert_eq!(Some(&2u128), state.balances.get(&treasury));
+        assert!(state.is_supply_consistent());
+
+        assert_eq!(0, state.transfer_fee(holder, receiver, 99));
+        assert_eq!(0, state.transfer_fee(holder, owner, 200));
+    }
+
+    #[test]
+    pub fn test_transfer_fee_transfer_from_and_bulk_transfer() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3],
+        };
+        let treasury = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = set_fee_config(
+            ctx,
+            state,
+            Some(FeeConfig {
+                rate_bps: 100,
+                recipient: treasury,
+            }),
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 500);
+        let ctx = create_ctx(holder);
+        let (state, _) = approve(ctx, state, receiver, 100, None);
+        let ctx = create_ctx(receiver);
+        let (state, _) = transfer_from(ctx, state, holder, receiver, 100);
+        assert_eq!(Some(&99u128), state.balances.get(&receiver));
+        assert_eq!(Some(&1u128), state.balances.get(&treasury));
+
+        let ctx = create_ctx(holder);
+        let (state, _) = bulk_transfer(
+            ctx,
+            state,
+            vec![
+                Transfer {
+                    to: receiver,
+                    amount: 100,
+                },
+                Transfer {
+                    to: receiver,
+                    amount: 300,
+                },
+            ],
+        );
+        assert_eq!(Some(&495u128), state.balances.get(&receiver));
+        assert_eq!(Some(&5u128), state.balances.get(&treasury));
+        assert!(state.is_supply_consistent());
+    }
+
+    #[test]
+    pub fn test_transfer_fee_exempt() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3],
+        };
+        let treasury = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = set_fee_config(
+            ctx,
+            state,
+            Some(FeeConfig {
+                rate_bps: 100,
+                recipient: treasury,
+            }),
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 500);
+        let ctx = create_ctx(owner);
+        let (state, _) = set_fee_exempt(ctx, state, receiver, true);
+        assert!(state.is_fee_exempt(receiver));
+        let ctx = create_ctx(holder);
+        let (state, _) = transfer(ctx, state, receiver, 200);
+        assert_eq!(Some(&200u128), state.balances.get(&receiver));
+        assert_eq!(None, state.balances.get(&treasury));
+
+        let ctx = create_ctx(owner);
+        let (state, _) = set_fee_exempt(ctx, state, receiver, false);
+        assert!(!state.is_fee_exempt(receiver));
+        assert!(state.is_fee_exempt(owner));
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-021]")]
+    pub fn test_set_fee_config_rate_too_high() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3],
+        };
+        let treasury = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = set_fee_config(
+            ctx,
+            state,
+            Some(FeeConfig {
+                rate_bps: 100,
+                recipient: treasury,
+            }),
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 500);
+        let ctx = create_ctx(owner);
+        set_fee_config(
+            ctx,
+            state,
+            Some(FeeConfig {
+                rate_bps: 10001,
+                recipient: treasury,
+            }),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-009]")]
+    pub fn test_set_fee_config_recipient_frozen() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let treasury = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = freeze(ctx, state, treasury);
+        let ctx = create_ctx(owner);
+        set_fee_config(
+            ctx,
+            state,
+            Some(FeeConfig {
+                rate_bps: 100,
+                recipient: treasury,
+            }),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-021]")]
+    pub fn test_freeze_fee_recipient() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let treasury = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            false,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = set_fee_config(
+            ctx,
+            state,
+            Some(FeeConfig {
+                rate_bps: 100,
+                recipient: treasury,
+            }),
+        );
+        let ctx = create_ctx(owner);
+        freeze(ctx, state, treasury);
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-010]")]
+    pub fn test_set_fee_config_recipient_not_allowlisted() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let treasury = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            true,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        set_fee_config(
+            ctx,
+            state,
+            Some(FeeConfig {
+                rate_bps: 100,
+                recipient: treasury,
+            }),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "[TOKEN-021]")]
+    pub fn test_remove_fee_recipient_from_allowlist() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let treasury = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            true,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = add_to_allowlist(ctx, state, vec![treasury]);
+        let ctx = create_ctx(owner);
+        let (state, _) = set_fee_config(
+            ctx,
+            state,
+            Some(FeeConfig {
+                rate_bps: 100,
+                recipient: treasury,
+            }),
+        );
+        let ctx = create_ctx(owner);
+        remove_from_allowlist(ctx, state, vec![treasury]);
+    }
+
+    #[test]
+    pub fn test_transfer_fee_recipient_cannot_receive() {
+        let owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+        let holder = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+        };
+        let receiver = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3],
+        };
+        let new_owner = Address {
+            address_type: AddressType::Account,
+            identifier: [0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5],
+        };
+        let ctx = create_ctx(owner);
+        let (state, _) = initialize(
+            ctx,
+            String::from("HelloToken"),
+            String::from("H$"),
+            0,
+            1000,
+            None,
+            true,
+            100,
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = add_to_allowlist(ctx, state, vec![holder, receiver]);
+        // The owner can always receive tokens, so it is accepted as the fee recipient.
+        let ctx = create_ctx(owner);
+        let (state, _) = set_fee_config(
+            ctx,
+            state,
+            Some(FeeConfig {
+                rate_bps: 100,
+                recipient: owner,
+            }),
+        );
+        let ctx = create_ctx(owner);
+        let (state, _) = transfer(ctx, state, holder, 500);
+        let ctx = create_ctx(owner);
+        let (state, _) = propose_owner(ctx, state, new_owner);
+        let ctx = create_ctx(new_owner);
+        let (state, _) = accept_ownership(ctx, state);
+        assert_eq!(0, state.transfer_fee(holder, receiver, 200));
+
+        let ctx = create_ctx(holder);
+        let (state, _) = transfer(ctx, state, receiver, 200);
+        assert_eq!(Some(&200u128), state.balances.get(&receiver));
+        assert_eq!(Some(&500u128), state.balances.get(&owner));
+        assert!(state.is_supply_consistent());
+    }
 }